@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: GPL-3.0-only
+use cosmic_applets_niri::workspaces::state::WorkspaceState;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use niri_ipc::Workspace;
+
+const OUTPUTS: [&str; 3] = ["DP-1", "DP-2", "HDMI-A-1"];
+
+/// 45 workspaces spread over three outputs, in the shuffled order niri may report them.
+fn snapshot() -> Vec<Workspace> {
+    let mut workspaces: Vec<Workspace> = (0..45u64)
+        .map(|id| Workspace {
+            id: id + 1,
+            idx: (id / 3 + 1) as u8,
+            name: (id % 4 == 0).then(|| format!("project-{id}")),
+            output: Some(OUTPUTS[id as usize % 3].to_owned()),
+            is_active: id < 3,
+            is_focused: id == 0,
+            active_window_id: Some(id * 7),
+        })
+        .collect();
+    workspaces.reverse();
+    workspaces
+}
+
+fn full_snapshot(c: &mut Criterion) {
+    let mut state = WorkspaceState::default();
+    c.bench_function("set_workspaces", |b| {
+        b.iter_batched(
+            snapshot,
+            |workspaces| state.set_workspaces(black_box(workspaces)),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn activation_delta(c: &mut Criterion) {
+    let mut state = WorkspaceState::default();
+    state.set_workspaces(snapshot());
+    let mut id = 0;
+    c.bench_function("activated", |b| {
+        b.iter(|| {
+            id = id % 45 + 1;
+            state.activated(black_box(id), true);
+        })
+    });
+}
+
+criterion_group!(benches, full_snapshot, activation_delta);
+criterion_main!(benches);
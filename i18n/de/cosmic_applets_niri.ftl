@@ -0,0 +1,62 @@
+workspace = Arbeitsfläche { $idx }
+workspace-named = Arbeitsfläche { $idx }: { $name }
+workspace-active = { $workspace }, aktiv
+workspace-focused = { $workspace }, fokussiert
+
+windows = { $count ->
+    [0] keine Fenster
+    [one] 1 Fenster
+   *[other] { $count } Fenster
+}
+windows-floating = { $windows } + { $floating } schwebend
+memory = ~{ $size } Arbeitsspeicher
+
+workspace-shortcut = Wechseln mit { $shortcut }
+switching = Wechsle…
+
+error-connect = Verbindung zu niri fehlgeschlagen
+connecting = Verbinde mit niri…
+error-action = Anfrage an niri fehlgeschlagen
+error-config = Einstellungen konnten nicht geladen werden
+crashed = abgestürzt: { $detail }
+
+version-mismatch = niri { $niri } wird eventuell nicht vollständig unterstützt, dieses Applet wurde für niri { $supported } gebaut
+
+niri-not-detected = niri-Compositor nicht gefunden
+niri-retry = Läuft niri nicht? Klicken, um erneut zu suchen
+
+columns = { $count ->
+    [0] keine Spalten
+    [one] 1 Spalte
+   *[other] { $count } Spalten
+}
+
+focus-lock-lock = Arbeitsflächenwechsel sperren
+focus-lock-unlock = Arbeitsflächenwechsel entsperren
+mute-start = Nichts mehr an niri senden
+mute-stop = Wieder Anfragen an niri senden
+history-back = Zurück zur vorherigen Arbeitsfläche
+history-forward = Vor zur nächsten Arbeitsfläche
+
+presentation-start = Präsentationsmodus starten
+presentation-stop = Präsentationsmodus beenden
+window-float = Fokussiertes Fenster schweben lassen
+window-tile = Fokussiertes Fenster kacheln
+
+last-window = zuletzt: { $app } — { $title }
+window-entry = { $app } — { $title }
+focus-previous-window = Vorheriges Fenster fokussieren
+copy-app-id = App-ID kopieren
+copy-title = Titel kopieren
+copied = { $text } kopiert
+unknown-app = Unbekannte App
+set-label = Beschriftung festlegen …
+rename-workspace = Name der Arbeitsfläche
+new-workspace-after = Neue Arbeitsfläche danach
+notify-window-opened = { $app } wurde auf { $workspace } geöffnet
+notify-workspace-created = { $workspace } wurde erstellt
+new-workspace = Neue Arbeitsfläche
+empty-workspace = Leere Arbeitsfläche
+
+no-focused-output = Keine Ausgabe
+focus-next-output = Nächste Ausgabe fokussieren
@@ -0,0 +1,62 @@
+workspace = Workspace { $idx }
+workspace-named = Workspace { $idx }: { $name }
+workspace-active = { $workspace }, active
+workspace-focused = { $workspace }, focused
+
+windows = { $count ->
+    [0] no windows
+    [one] 1 window
+   *[other] { $count } windows
+}
+windows-floating = { $windows } + { $floating } floating
+memory = ~{ $size } memory
+
+workspace-shortcut = Switch with { $shortcut }
+switching = Switching…
+
+error-connect = Failed to connect to niri
+connecting = Connecting to niri…
+error-action = Failed to send a request to niri
+error-config = Failed to load the settings
+crashed = crashed: { $detail }
+
+version-mismatch = niri { $niri } may not be fully supported, this applet was built for niri { $supported }
+
+niri-not-detected = niri compositor not detected
+niri-retry = Not running under niri? Click to look for it again
+
+columns = { $count ->
+    [0] no columns
+    [one] 1 column
+   *[other] { $count } columns
+}
+
+focus-lock-lock = Lock workspace switching
+focus-lock-unlock = Unlock workspace switching
+mute-start = Stop sending anything to niri
+mute-stop = Resume sending requests to niri
+history-back = Back to the previous workspace
+history-forward = Forward to the next workspace
+
+presentation-start = Start presentation mode
+presentation-stop = Stop presentation mode
+window-float = Float the focused window
+window-tile = Tile the focused window
+
+last-window = last: { $app } — { $title }
+window-entry = { $app } — { $title }
+focus-previous-window = Focus previous window
+copy-app-id = Copy app ID
+copy-title = Copy title
+copied = Copied { $text }
+unknown-app = Unknown app
+set-label = Set label…
+rename-workspace = Workspace name
+new-workspace-after = New workspace after this
+notify-window-opened = { $app } opened on { $workspace }
+notify-workspace-created = { $workspace } was created
+new-workspace = New workspace
+empty-workspace = Empty workspace
+
+no-focused-output = No output
+focus-next-output = Focus the next output
@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::backtrace::Backtrace;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::panic;
+use std::time::SystemTime;
+
+use crate::workspaces::state_dir;
+
+/// Logs panics with a backtrace to stderr and appends them to
+/// `$XDG_STATE_HOME/cosmic-applet-niri/crash.log`, since the panel doesn't show the applet's
+/// output anywhere obvious.
+pub fn install_panic_hook() {
+    panic::set_hook(Box::new(|info| {
+        let report = format!("{info}\n{}", Backtrace::force_capture());
+        eprintln!("{report}");
+        if let Err(e) = append(&report) {
+            eprintln!("failed to write the crash log: {e}");
+        }
+    }));
+}
+
+fn append(report: &str) -> io::Result<()> {
+    let Some(dir) = state_dir() else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "neither $XDG_STATE_HOME nor $HOME is set",
+        ));
+    };
+    fs::create_dir_all(&dir)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join("crash.log"))?;
+    let time = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or_default();
+    writeln!(file, "--- crashed at {time} (unix time)\n{report}")
+}
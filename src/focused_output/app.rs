@@ -0,0 +1,219 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::app::{Core, Task};
+use cosmic::iced::futures::{future, StreamExt};
+use cosmic::iced::Subscription;
+use cosmic::widget;
+use cosmic::{Application, Element};
+use niri_ipc::{Action, Workspace};
+use std::collections::HashMap;
+use std::sync::mpsc;
+
+use crate::fl;
+use crate::workspaces::niri::{self, ConnectionState, Discovery};
+use crate::workspaces::state::WorkspaceState;
+use crate::workspaces::{self, Config, EventCategories, WorkspacesApp};
+
+/// Applet showing the name of the output with keyboard focus, which clicks move on.
+///
+/// Shares its settings with the workspaces applet, see [`Config::output_aliases`].
+pub struct FocusedOutputApp {
+    core: Core,
+    config: Config,
+    state: WorkspaceState,
+    /// Actions for niri, once connected.
+    sender: Option<mpsc::Sender<Action>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Ready(mpsc::Sender<Action>),
+    WorkspaceUpdate(Vec<Workspace>),
+    WorkspaceActivated { id: u64, focused: bool },
+    FocusNextOutput,
+    ConfigChanged(Config),
+}
+
+impl FocusedOutputApp {
+    fn discovery(&self) -> Discovery {
+        Discovery {
+            session: self.config.niri_session.clone(),
+            socket_path: self.config.niri_socket.clone(),
+        }
+    }
+}
+
+impl Application for FocusedOutputApp {
+    type Executor = cosmic::SingleThreadExecutor;
+
+    type Flags = ();
+
+    type Message = Message;
+
+    const APP_ID: &'static str = "com.niri.focused-output";
+
+    fn core(&self) -> &Core {
+        &self.core
+    }
+
+    fn core_mut(&mut self) -> &mut Core {
+        &mut self.core
+    }
+
+    fn init(core: Core, _flags: Self::Flags) -> (Self, Task<Self::Message>) {
+        let (_, config, errors) = Config::load(WorkspacesApp::APP_ID);
+        for error in errors {
+            eprintln!("{}: {error}", fl!("error-config"));
+        }
+        let app = FocusedOutputApp {
+            core,
+            config,
+            state: WorkspaceState::default(),
+            sender: None,
+        };
+        (app, Task::none())
+    }
+
+    fn view(&self) -> Element<Self::Message> {
+        let name = label(&self.state, &self.config.output_aliases)
+            .unwrap_or_else(|| fl!("no-focused-output"));
+        let button = widget::button::custom(widget::text::body(name))
+            .class(cosmic::theme::Button::AppletIcon)
+            .name(fl!("focus-next-output"))
+            .on_press(Message::FocusNextOutput);
+        self.core.applet.autosize_window(button).into()
+    }
+
+    fn update(&mut self, message: Self::Message) -> Task<Self::Message> {
+        match message {
+            Message::Ready(sender) => self.sender = Some(sender),
+            Message::WorkspaceUpdate(workspaces) => self.state.set_workspaces(workspaces),
+            // activations on other outputs don't move the focus
+            Message::WorkspaceActivated { id, focused: true } => self.state.activated(id, true),
+            Message::WorkspaceActivated { .. } => {}
+            Message::FocusNextOutput => {
+                let sent = self
+                    .sender
+                    .as_ref()
+                    .is_some_and(|sender| sender.send(Action::FocusMonitorNext {}).is_ok());
+                if !sent {
+                    eprintln!("{}: not connected to niri", fl!("error-action"));
+                }
+            }
+            Message::ConfigChanged(config) => self.config = config,
+        }
+        Task::none()
+    }
+
+    fn subscription(&self) -> Subscription<Self::Message> {
+        let discovery = self.discovery();
+        // the workspaces applet's connection, with its backoff and wait for niri at login
+        let events = niri::sub(Vec::new(), discovery.clone(), WORKSPACE_EVENTS)
+            .filter_map(|message| future::ready(from_niri(message)));
+        Subscription::batch([
+            Subscription::run_with_id(("focused-output", discovery), events),
+            self.core
+                .watch_config::<Config>(WorkspacesApp::APP_ID)
+                .map(|update| Message::ConfigChanged(update.config)),
+        ])
+    }
+
+    fn style(&self) -> Option<cosmic::iced_runtime::Appearance> {
+        Some(cosmic::applet::style())
+    }
+}
+
+/// Name of the output with keyboard focus, or its alias in `aliases`.
+fn label(state: &WorkspaceState, aliases: &HashMap<String, String>) -> Option<String> {
+    let output = state.focused()?.output.as_deref()?;
+    let alias = aliases.get(output).map_or(output, String::as_str);
+    Some(alias.to_owned())
+}
+
+/// The only events the applet needs, window events aren't subscribed to.
+const WORKSPACE_EVENTS: EventCategories = EventCategories {
+    workspaces: true,
+    windows: false,
+};
+
+/// The messages of the workspaces applet's niri connection this applet handles, logging its
+/// errors as there's nowhere to show them.
+fn from_niri(message: workspaces::Message) -> Option<Message> {
+    match message {
+        workspaces::Message::Ready(sender) => Some(Message::Ready(sender)),
+        workspaces::Message::WorkspaceUpdate(workspaces) => {
+            Some(Message::WorkspaceUpdate(workspaces))
+        }
+        workspaces::Message::WorkspaceActivated { id, focused } => {
+            Some(Message::WorkspaceActivated { id, focused })
+        }
+        workspaces::Message::Error { context, detail } => {
+            eprintln!("{context}: {detail}");
+            None
+        }
+        workspaces::Message::ConnectionStateChanged(ConnectionState::Error(detail)) => {
+            eprintln!("{}: {detail}", fl!("error-connect"));
+            None
+        }
+        workspaces::Message::NiriMissing => {
+            eprintln!("niri's socket was not found, still looking for it");
+            None
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(id: u64, output: &str, is_focused: bool) -> Workspace {
+        Workspace {
+            id,
+            idx: 1,
+            name: None,
+            output: Some(output.to_owned()),
+            is_active: true,
+            is_focused,
+            active_window_id: None,
+        }
+    }
+
+    #[test]
+    fn labels_the_focused_output() {
+        let mut state = WorkspaceState::default();
+        let aliases = HashMap::from([("HDMI-A-1".to_owned(), "TV".to_owned())]);
+        assert_eq!(label(&state, &aliases), None);
+
+        state.set_workspaces(vec![
+            workspace(1, "DP-1", true),
+            workspace(2, "HDMI-A-1", false),
+        ]);
+        assert_eq!(label(&state, &aliases).as_deref(), Some("DP-1"));
+
+        state.activated(2, true);
+        assert_eq!(label(&state, &aliases).as_deref(), Some("TV"));
+    }
+
+    #[test]
+    fn keeps_only_workspace_messages() {
+        let update = workspaces::Message::WorkspaceUpdate(vec![workspace(1, "DP-1", true)]);
+        assert!(matches!(
+            from_niri(update),
+            Some(Message::WorkspaceUpdate(workspaces)) if workspaces.len() == 1
+        ));
+        let activated = workspaces::Message::WorkspaceActivated {
+            id: 1,
+            focused: true,
+        };
+        assert!(matches!(
+            from_niri(activated),
+            Some(Message::WorkspaceActivated {
+                id: 1,
+                focused: true
+            })
+        ));
+        let connected = workspaces::Message::ConnectionStateChanged(ConnectionState::Connected);
+        assert!(from_niri(connected).is_none());
+    }
+}
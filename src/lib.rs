@@ -0,0 +1,5 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pub mod crash;
+pub mod focused_output;
+pub mod i18n;
+pub mod workspaces;
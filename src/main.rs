@@ -1,6 +1,21 @@
 // SPDX-License-Identifier: GPL-3.0-only
-mod workspaces;
+use cosmic_applets_niri::{crash, focused_output, i18n, workspaces};
+use std::path::Path;
 
 fn main() -> cosmic::iced::Result {
-    cosmic::applet::run::<workspaces::WorkspacesApp>(())
+    crash::install_panic_hook();
+    let requested_languages = i18n_embed::DesktopLanguageRequester::requested_languages();
+    i18n::init(&requested_languages);
+
+    // all applets share one binary, linked under the name of each applet
+    let program = std::env::args().next().unwrap_or_default();
+    match Path::new(&program)
+        .file_name()
+        .and_then(|name| name.to_str())
+    {
+        Some("niri-applet-focused-output") => {
+            cosmic::applet::run::<focused_output::FocusedOutputApp>(())
+        }
+        _ => cosmic::applet::run::<workspaces::WorkspacesApp>(()),
+    }
 }
@@ -2,29 +2,1413 @@
 
 use cosmic::app::{Core, Task};
 use cosmic::applet::cosmic_panel_config::PanelAnchor;
-use cosmic::iced::{Length, Subscription};
-use cosmic::widget;
+use cosmic::cosmic_config;
+use cosmic::iced::keyboard::{self, key::Named, Key, Modifiers};
+use cosmic::iced::mouse::{self, ScrollDelta};
+use cosmic::iced::platform_specific::shell::commands::popup::{destroy_popup, get_popup};
+use cosmic::iced::widget::mouse_area;
+use cosmic::iced::{
+    clipboard, event, time, window, Alignment, Color, Length, Padding, Point, Subscription,
+};
+use cosmic::widget::{self, tooltip};
 use cosmic::{Application, Element};
-use niri_ipc::Workspace;
+use niri_ipc::{Action, Window, Workspace, WorkspaceReferenceArg};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::TAU;
+use std::path::PathBuf;
 use std::sync::mpsc;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
 
-use super::niri;
+use super::binds::Shortcuts;
+use super::cache::SnapshotCache;
+use super::config::{
+    ActionButton, Config, DoubleClickAction, EasingCurve, FloatingWindows, HeatmapColors,
+    Placement, ScrollAction, Sizing, WorkspaceEvent,
+};
+use super::history::History;
+use super::hook;
+use super::icons::{self, IconCache};
+use super::memory;
+use super::niri::{self, ConnectionState};
+use super::notify;
+use super::order::{self, OrderMemory};
+use super::power;
+use super::render::{self, AppIcon, Indicator, IndicatorStyle, Metrics, RippleState};
+use super::state::{self, WorkspaceState};
+use super::style;
+use super::usage::Usage;
+use crate::fl;
+
+/// Interval between animation frames.
+const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+/// Time the stealth mode fade takes from invisible to fully opaque.
+const STEALTH_FADE: Duration = Duration::from_millis(200);
+/// Time a button takes to grow in when it appears, or to grow or shrink in zen mode.
+const RESIZE: Duration = Duration::from_millis(200);
+/// Time the ripple of a pressed button takes to fade out.
+const RIPPLE: Duration = Duration::from_millis(300);
+/// Time a button waits for niri to confirm a switch before it stops showing it as pending.
+const PENDING_TIMEOUT: Duration = Duration::from_secs(1);
+/// Length of a button along the panel.
+const BUTTON_LENGTH: f32 = 16.0;
+/// Size of the buttons in presentation mode relative to their usual size.
+const PRESENTATION_SCALE: f32 = 2.0;
+/// Space between buttons.
+const SPACING: f32 = 4.0;
+/// Space around the applet.
+const PADDING: u16 = 8;
+/// Default bounds for the thickness of buttons derived from the panel size.
+const MIN_THICKNESS: f32 = 12.0;
+const MAX_THICKNESS: f32 = 64.0;
+/// Smallest thickness of a line of buttons in the wrapped layout.
+const MIN_LINE_THICKNESS: f32 = 16.0;
+/// Time an error stays visible unless dismissed earlier.
+const ERROR_TIMEOUT: Duration = Duration::from_secs(30);
+/// Opacity of the buttons while niri is disconnected.
+const OFFLINE_OPACITY: f32 = 0.5;
+/// Opacity of the buttons showing the cached workspaces, before niri reports the real ones.
+const STALE_OPACITY: f32 = 0.7;
+/// Time the workspaces have to stay unchanged before they're cached, see [`SnapshotCache`].
+const SNAPSHOT_DELAY: Duration = Duration::from_secs(2);
+/// First niri release with the focus-window-previous action.
+const FOCUS_PREVIOUS_SINCE: (u32, u32) = (25, 2);
+/// Most window icons stacked on a button.
+const STACKED_ICONS: usize = 3;
+/// Default time in which activating the same workspace again is ignored.
+const ACTIVATION_DEBOUNCE: Duration = Duration::from_millis(150);
+/// Default time after a notification in which no other one is shown.
+const NOTIFY_COOLDOWN: Duration = Duration::from_secs(5);
+/// Pixels of touchpad scrolling that count as one scroll step.
+const SCROLL_LINE: f32 = 20.0;
+/// Longest time between the clicks of a double click.
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+/// Width of the text field renaming a workspace in the panel, widening the applet while open.
+const RENAME_WIDTH: f32 = 120.0;
+/// The text field renaming a workspace, to focus it.
+static RENAME_INPUT: LazyLock<widget::Id> = LazyLock::new(|| widget::Id::new("rename"));
+/// Time a change of the shown workspaces has to last before the applet resizes for it, so
+/// workspaces that only flash by don't make the panel jitter.
+const LAYOUT_DEBOUNCE: Duration = Duration::from_millis(100);
+/// Time the confirmation of copying to the clipboard stays in the window list.
+const COPIED_TIMEOUT: Duration = Duration::from_secs(2);
+/// Time after the last typed digit that a workspace number is jumped to.
+const NUMBER_ENTRY_TIMEOUT: Duration = Duration::from_millis(800);
+/// Most digits a typed workspace number can have, niri's indices fit in a `u8`.
+const NUMBER_ENTRY_DIGITS: usize = 3;
+
+/// An error shown to the user.
+struct ErrorReport {
+    context: String,
+    detail: String,
+    time: Instant,
+}
 
 /// This is the struct that represents your application.
 /// It is used to define the data that will be used by your application.
 pub struct WorkspacesApp {
     /// Application state which is managed by the COSMIC runtime.
     core: Core,
-    workspaces: Vec<Workspace>,
-    sender: Option<mpsc::Sender<u64>>,
+    config: Config,
+    /// Writes settings changed from the applet back, if the config could be opened.
+    config_handler: Option<cosmic_config::Config>,
+    state: WorkspaceState,
+    order: OrderMemory,
+    /// Workspace shortcuts read from niri's config, if configured.
+    shortcuts: Shortcuts,
+    /// Action buttons from the config whose actions are valid.
+    actions: Vec<(ActionButton, Action)>,
+    /// App icons looked up while drawing.
+    icons: RefCell<IconCache>,
+    sender: Option<mpsc::Sender<Action>>,
+    /// Workspace switches from the applet are ignored, see [`Message::ToggleFocusLock`].
+    focus_locked: bool,
+    /// Nothing is sent to niri, see [`Message::ToggleMute`].
+    muted: bool,
+    /// Power saving is on, see [`Config::monochrome_when_power_saving`].
+    power_saving: bool,
+    /// Keyboard modifiers currently held down.
+    modifiers: Modifiers,
+    /// Reference point of the running animations.
+    animation_epoch: Instant,
+    /// Opacity of the neon glow around the active workspace, pulsing between 0.6 and 1.0.
+    active_glow_strength: f32,
+    /// Time of the last animation frame.
+    last_frame: Instant,
+    pointer_in_applet: bool,
+    /// Workspace button under the pointer and where on it the pointer is, for ripples.
+    pointer: Option<(u64, Point)>,
+    /// Opacity of the buttons in stealth mode, fading towards 1.0 while hovered.
+    stealth_opacity: f32,
+    /// Size of every button relative to its full size, animating towards its target scale.
+    scales: HashMap<u64, f32>,
+    /// Ripple of every recently pressed workspace button.
+    ripples: HashMap<u64, RippleState>,
+    /// Workspaces switched to that niri hasn't confirmed yet, with the time of the request.
+    pending: HashMap<u64, Instant>,
+    /// Most recent error, shown as an icon until it's clicked or times out.
+    error: Option<ErrorReport>,
+    /// niri's version and the supported one, while the mismatch warning isn't dismissed.
+    version_mismatch: Option<(String, String)>,
+    /// niri's major and minor version, if it differs from the supported one.
+    niri_version: Option<(u32, u32)>,
+    /// No niri socket was found, the session is probably running another compositor.
+    niri_missing: bool,
+    /// Bumped to restart the niri connection right away, see [`Message::RetryNiri`].
+    retries: u32,
+    connection: ConnectionState,
+    /// The next workspace snapshot follows a reconnect and may come with new ids, see
+    /// [`Self::migrate`].
+    reconnected: bool,
+    /// Popup listing the windows of a workspace, pinned open by right-clicking its button.
+    window_list: Option<(window::Id, u64)>,
+    /// App ids whose windows are folded away in the pinned window list, while it's open.
+    collapsed_apps: HashSet<String>,
+    /// Text last copied from the window list and when, shown there for [`COPIED_TIMEOUT`].
+    copied: Option<(String, Instant)>,
+    /// Workspaces as last laid out, drawn until a change of the displayed ones settles, see
+    /// [`LAYOUT_DEBOUNCE`].
+    laid_out: Vec<Workspace>,
+    /// Since when the displayed workspaces differ from `laid_out`.
+    layout_changed: Option<Instant>,
+    /// Workspaces of the last session on this output, see [`SnapshotCache`].
+    snapshot_cache: SnapshotCache,
+    /// Whether the workspaces are still the cached ones, until niri's first snapshot.
+    stale: bool,
+    /// Since when the workspaces changed without being cached, see [`SNAPSHOT_DELAY`].
+    unsaved_since: Option<Instant>,
+    /// Most workspaces shown at once this session, see [`Sizing::Stable`].
+    most_shown: usize,
+    /// Label being typed into the pinned window list, see [`Config::labels`].
+    label_draft: String,
+    /// niri name being typed into the pinned window list.
+    name_draft: String,
+    /// Workspace being renamed in the panel and the name typed so far, see
+    /// [`DoubleClickAction::Rename`].
+    renaming: Option<(u64, String)>,
+    /// Time of the last notification, see [`Config::notify_cooldown_ms`].
+    last_notification: Option<Instant>,
+    /// Popup offering an empty workspace and the configured templates.
+    template_menu: Option<window::Id>,
+    /// Workspace last activated from the applet and when, to ignore accidental repeats.
+    last_activation: Option<(u64, Instant)>,
+    /// Workspace button clicked last and when, while waiting for a possible second click.
+    click: Option<(u64, Instant)>,
+    /// Scrolling left over from the last scroll events, less than one step.
+    scroll_remainder: f32,
+    /// Memory used by the processes of a workspace's windows, as of the pointer last getting
+    /// onto its button.
+    memory: HashMap<u64, u64>,
+    /// Time spent on every workspace, for the heatmap.
+    usage: Usage,
+    /// Workspaces focused before, for the history buttons.
+    history: History,
+    /// Digits of a workspace number typed while the applet has keyboard focus, with the time
+    /// of the last one.
+    number_entry: Option<(String, Instant)>,
 }
 impl WorkspacesApp {
-    pub fn new(core: Core) -> Self {
+    pub fn new(core: Core, config: Config) -> Self {
         Self {
             core,
-            workspaces: Vec::new(),
+            shortcuts: Self::load_shortcuts(&config),
+            actions: Self::parse_actions(&config),
+            config,
+            config_handler: None,
+            state: WorkspaceState::default(),
+            order: OrderMemory::load(),
+            icons: RefCell::default(),
             sender: None,
+            focus_locked: false,
+            muted: false,
+            power_saving: false,
+            modifiers: Modifiers::empty(),
+            animation_epoch: Instant::now(),
+            active_glow_strength: 1.0,
+            last_frame: Instant::now(),
+            pointer_in_applet: false,
+            pointer: None,
+            stealth_opacity: 0.0,
+            scales: HashMap::new(),
+            ripples: HashMap::new(),
+            pending: HashMap::new(),
+            error: None,
+            version_mismatch: None,
+            niri_version: None,
+            niri_missing: false,
+            retries: 0,
+            connection: ConnectionState::Disconnected,
+            window_list: None,
+            collapsed_apps: HashSet::new(),
+            copied: None,
+            laid_out: Vec::new(),
+            layout_changed: None,
+            snapshot_cache: SnapshotCache::default(),
+            stale: false,
+            unsaved_since: None,
+            most_shown: 0,
+            label_draft: String::new(),
+            name_draft: String::new(),
+            renaming: None,
+            last_notification: None,
+            template_menu: None,
+            reconnected: false,
+            last_activation: None,
+            click: None,
+            scroll_remainder: 0.0,
+            memory: HashMap::new(),
+            usage: Usage::default(),
+            history: History::default(),
+            number_entry: None,
+        }
+    }
+
+    /// Accessible name of a workspace indicator, e.g. "Workspace 3, active".
+    fn accessible_name(workspace: &Workspace) -> String {
+        let name = match &workspace.name {
+            Some(name) => fl!("workspace-named", idx = workspace.idx, name = name.as_str()),
+            None => fl!("workspace", idx = workspace.idx),
+        };
+        if workspace.is_focused {
+            fl!("workspace-focused", workspace = name)
+        } else if workspace.is_active {
+            fl!("workspace-active", workspace = name)
+        } else {
+            name
+        }
+    }
+
+    fn parse_actions(config: &Config) -> Vec<(ActionButton, Action)> {
+        let buttons = config.action_buttons.iter();
+        buttons
+            .filter_map(|button| Some((button.clone(), button.action()?)))
+            .collect()
+    }
+
+    /// Button toggling the focus lock, showing whether it's locked.
+    fn focus_lock_button(&self) -> Element<Message> {
+        let (icon, name) = match self.focus_locked {
+            true => ("changes-prevent-symbolic", fl!("focus-lock-unlock")),
+            false => ("changes-allow-symbolic", fl!("focus-lock-lock")),
+        };
+        let icon = widget::icon::from_name(icon).size(16).icon();
+        widget::button::custom(icon)
+            .class(self.faded(cosmic::theme::Button::Text))
+            .name(name)
+            .on_press(Message::ToggleFocusLock)
+            .into()
+    }
+
+    /// Buttons going back and forward through the focused workspaces, disabled when there's
+    /// nowhere to go or switching is locked.
+    fn history_buttons(&self) -> [Element<Message>; 2] {
+        let exists = |id| self.state.workspace(id).is_some();
+        let button = |icon: &str, name: String, message, enabled: bool| -> Element<Message> {
+            let icon = widget::icon::from_name(icon).size(16).icon();
+            widget::button::custom(icon)
+                .class(self.faded(cosmic::theme::Button::Text))
+                .name(name)
+                .on_press_maybe((enabled && !self.focus_locked).then_some(message))
+                .into()
+        };
+        [
+            button(
+                "go-previous-symbolic",
+                fl!("history-back"),
+                Message::HistoryBack,
+                self.history.can_go_back(exists),
+            ),
+            button(
+                "go-next-symbolic",
+                fl!("history-forward"),
+                Message::HistoryForward,
+                self.history.can_go_forward(exists),
+            ),
+        ]
+    }
+
+    /// Button muting the applet's requests to niri, highlighted while muted.
+    fn mute_toggle(&self) -> Element<Message> {
+        let (name, class) = match self.muted {
+            true => (fl!("mute-stop"), cosmic::theme::Button::Suggested),
+            false => (fl!("mute-start"), cosmic::theme::Button::Text),
+        };
+        let icon = widget::icon::from_name("system-lock-screen-symbolic")
+            .size(16)
+            .icon();
+        widget::button::custom(icon)
+            .class(self.faded(class))
+            .name(name)
+            .on_press(Message::ToggleMute)
+            .into()
+    }
+
+    fn presentation_toggle(&self) -> Element<Message> {
+        let name = match self.config.presentation_mode {
+            true => fl!("presentation-stop"),
+            false => fl!("presentation-start"),
+        };
+        let icon = widget::icon::from_name("video-display-symbolic")
+            .size(16)
+            .icon();
+        let class = match self.config.presentation_mode {
+            true => cosmic::theme::Button::Suggested,
+            false => cosmic::theme::Button::Text,
+        };
+        widget::button::custom(icon)
+            .class(self.faded(class))
+            .name(name)
+            .on_press(Message::TogglePresentation)
+            .into()
+    }
+
+    /// Button floating or tiling the focused window, highlighted while it floats.
+    fn floating_toggle(&self) -> Element<Message> {
+        let window = self.state.focused_window();
+        let floating = window.is_some_and(|window| window.is_floating);
+        let name = match floating {
+            true => fl!("window-tile"),
+            false => fl!("window-float"),
+        };
+        let icon = widget::icon::from_name("window-pop-out-symbolic")
+            .size(16)
+            .icon();
+        let class = match floating {
+            true => cosmic::theme::Button::Suggested,
+            false => cosmic::theme::Button::Text,
+        };
+        widget::button::custom(icon)
+            .class(self.faded(class))
+            .name(name)
+            .on_press_maybe(window.map(|window| Message::ToggleFloating(window.id)))
+            .into()
+    }
+
+    /// Buttons running the configured niri actions.
+    fn action_buttons(&self) -> impl Iterator<Item = Element<Message>> {
+        self.actions.iter().enumerate().map(|(i, (button, _))| {
+            let content: Element<Message> = match &button.icon {
+                Some(icon) => widget::icon::from_name(icon.as_str())
+                    .size(16)
+                    .icon()
+                    .into(),
+                None => widget::text::body(button.label.clone()).into(),
+            };
+            widget::button::custom(content)
+                .class(self.faded(cosmic::theme::Button::Standard))
+                .name(button.label.clone())
+                .on_press(Message::RunAction(i))
+                .into()
+        })
+    }
+
+    /// Button opening the new workspace menu.
+    fn new_workspace_button(&self) -> Element<Message> {
+        let icon = widget::icon::from_name("list-add-symbolic").size(16).icon();
+        widget::button::custom(icon)
+            .class(self.faded(cosmic::theme::Button::Standard))
+            .name(fl!("new-workspace"))
+            .on_press(Message::ToggleTemplateMenu)
+            .into()
+    }
+
+    fn toggle_template_menu(&mut self) -> Task<Message> {
+        if let Some(popup) = self.template_menu.take() {
+            return destroy_popup(popup);
+        }
+        let Some(parent) = self.core.main_window_id() else {
+            return Task::none();
+        };
+        let popup = window::Id::unique();
+        self.template_menu = Some(popup);
+        let settings = self
+            .core
+            .applet
+            .get_popup_settings(parent, popup, None, None, None);
+        get_popup(settings)
+    }
+
+    fn template_menu_content(&self) -> Element<Message> {
+        let mut menu = widget::column::with_capacity(self.config.workspace_templates.len() + 1)
+            .spacing(4)
+            .padding(8)
+            .push(
+                widget::button::text(fl!("empty-workspace"))
+                    .on_press(Message::CreateWorkspace(None)),
+            );
+        for (i, template) in self.config.workspace_templates.iter().enumerate() {
+            menu = menu.push(
+                widget::button::text(template.name.clone())
+                    .on_press(Message::CreateWorkspace(Some(i))),
+            );
+        }
+        menu.into()
+    }
+
+    /// Focuses the empty workspace at the end of the focused output, then names it and starts
+    /// the apps of the template at `template` in the config, if given.
+    fn create_workspace(&mut self, template: Option<usize>) -> Task<Message> {
+        let focused_output = self.state.focused().and_then(|w| w.output.clone());
+        let empty = self
+            .state
+            .workspaces()
+            .iter()
+            .find(|w| w.output == focused_output && self.state.is_trailing_empty(w))
+            .map(|w| w.id);
+        let Some(id) = empty.filter(|&id| self.can_activate(id)) else {
+            return Task::none();
+        };
+        let task = self.update(Message::ActivateWorkspace(id));
+        let Some(template) = template.and_then(|i| self.config.workspace_templates.get(i)) else {
+            return task;
+        };
+        let taken: Vec<&str> = self
+            .state
+            .workspaces()
+            .iter()
+            .filter_map(|w| w.name.as_deref())
+            .collect();
+        let name = numbered_name(&template.create_name, &taken);
+        let apps = template.initial_apps.clone();
+        self.send(Action::SetWorkspaceName {
+            name,
+            workspace: Some(WorkspaceReferenceArg::Id(id)),
+        });
+        // niri handles actions in order, so the apps start on the new workspace
+        for app in apps {
+            self.send(Action::Spawn {
+                command: vec!["sh".to_owned(), "-c".to_owned(), app],
+            });
+        }
+        task
+    }
+
+    fn load_shortcuts(config: &Config) -> Shortcuts {
+        match &config.niri_config {
+            Some(path) => Shortcuts::load(path),
+            None => Shortcuts::default(),
+        }
+    }
+
+    /// App id whose icon marks `workspace` as the target of an app rule, if enabled.
+    fn app_target(&self, workspace: &Workspace) -> Option<&str> {
+        if !self.config.show_app_targets {
+            return None;
+        }
+        let apps = self.config.app_targets.get(workspace.name.as_ref()?)?;
+        apps.first().map(String::as_str)
+    }
+
+    fn stacks_icons(&self) -> bool {
+        self.config.show_window_icons && self.config.stack_window_icons
+    }
+
+    /// Windows on `workspace_id`, the last focused one first and the rest by id.
+    fn sorted_windows(&self, workspace_id: u64) -> Vec<&Window> {
+        let active = self.state.active_window(workspace_id);
+        let mut windows: Vec<&Window> = self.state.windows_on(workspace_id).collect();
+        windows.sort_by_key(|w| (Some(w.id) != active, w.id));
+        windows
+    }
+
+    /// Looks up the icons of the open windows' apps and any others drawn without their icon
+    /// in the background, keeping icon theme searches off the UI thread.
+    fn load_icons(&mut self) -> Task<Message> {
+        let cache = self.icons.get_mut();
+        cache.request(self.state.windows().filter_map(|w| w.app_id.as_deref()));
+        let app_ids = cache.take_missing();
+        if app_ids.is_empty() {
+            return Task::none();
+        }
+        let theme = cache.theme().to_owned();
+        cosmic::task::future(async move {
+            let found = tokio::task::spawn_blocking(move || {
+                let found = app_ids.into_iter().map(|app_id| {
+                    let path = icons::find(&app_id);
+                    (app_id, path)
+                });
+                found.collect()
+            });
+            Message::IconsLoaded {
+                theme,
+                found: found.await.unwrap_or_default(),
+            }
+        })
+    }
+
+    /// What's drawn for each shown workspace, in display order. Workspaces shrunk away
+    /// entirely are left out.
+    fn indicators(&self) -> Vec<Indicator> {
+        let workspaces = self.shown();
+        let heat = self.config.heatmap.then(|| {
+            let ids: Vec<u64> = workspaces.iter().map(|w| w.id).collect();
+            self.usage.heat(&ids, Instant::now())
+        });
+        let numbers =
+            !self.config.presentation_mode && self.config.indicator_style == IndicatorStyle::Number;
+        let mut indicators = Vec::with_capacity(workspaces.len());
+        for workspace in &workspaces {
+            let scale = self.scale(workspace);
+            if scale == 0.0 {
+                continue;
+            }
+            let (icons, overflow) = self.app_icons(workspace);
+            indicators.push(Indicator {
+                id: workspace.id,
+                label: match (self.config.label(workspace), &workspace.name) {
+                    _ if numbers => workspace.idx.to_string(),
+                    (Some(label), _) => label.to_owned(),
+                    (None, Some(name)) => name.clone(),
+                    (None, None) => workspace.idx.to_string(),
+                },
+                accessible_name: Self::accessible_name(workspace),
+                active: workspace.is_active,
+                focused: workspace.is_focused,
+                hollow: self.config.outline_empty && self.state.is_empty(workspace.id),
+                icons,
+                overflow,
+                scale,
+                badge: self.corner_badge(workspace),
+                window_count: self.window_badge(workspace),
+                ripple: self.ripples.get(&workspace.id).copied(),
+                shimmer: self.shimmer(workspace),
+                heat: heat
+                    .as_ref()
+                    .and_then(|heat| heat.get(&workspace.id).copied()),
+                tooltip: self.tooltip(workspace),
+                on_press: self.on_press(workspace),
+            });
+        }
+        indicators
+    }
+
+    /// Icons for the button of `workspace` and the number of windows left out of them.
+    fn app_icons(&self, workspace: &Workspace) -> (Vec<AppIcon>, usize) {
+        let icon = |app_id: &str| AppIcon::new(app_id, self.icons.borrow_mut().get(app_id));
+        if let Some(app_id) = self.app_target(workspace) {
+            return (vec![icon(app_id)], 0);
+        }
+        if self.stacks_icons() {
+            let windows = self.sorted_windows(workspace.id);
+            let icons = windows
+                .iter()
+                .take(STACKED_ICONS)
+                .map(|w| icon(w.app_id.as_deref().unwrap_or_default()))
+                .collect();
+            return (icons, windows.len().saturating_sub(STACKED_ICONS));
+        }
+        let last_app = self
+            .last_window(workspace)
+            .and_then(|w| w.app_id.as_deref());
+        (last_app.map(icon).into_iter().collect(), 0)
+    }
+
+    /// The window last focused on `workspace`, if window icons are enabled.
+    fn last_window(&self, workspace: &Workspace) -> Option<&Window> {
+        if !self.config.show_window_icons {
+            return None;
+        }
+        self.state.window(self.state.active_window(workspace.id)?)
+    }
+
+    /// Hover text of `workspace`: a pending switch, its shortcut, window count, memory use, column
+    /// count and its last or all windows, as far as they're enabled.
+    fn tooltip(&self, workspace: &Workspace) -> Option<String> {
+        let switching = self.is_switching(workspace).then(|| fl!("switching"));
+        let shortcut = self.shortcuts.get(workspace);
+        let shortcut = shortcut.map(|shortcut| fl!("workspace-shortcut", shortcut = shortcut));
+        let windows = self.config.show_window_counts.then(|| {
+            self.window_count(
+                self.state.tiled_window_count(workspace.id),
+                self.state.floating_window_count(workspace.id),
+            )
+        });
+        let memory = match self.config.show_resource_usage {
+            true => self.memory.get(&workspace.id),
+            false => None,
+        };
+        let memory = memory.map(|&bytes| fl!("memory", size = memory::format(bytes)));
+        // niri-ipc 25.2 has no column layout, every tiled window is counted as a column
+        let columns = self.config.show_column_counts.then(|| {
+            let count = self.state.tiled_window_count(workspace.id);
+            fl!("columns", count = count)
+        });
+        let last_window = match self.stacks_icons() {
+            true => None,
+            false => self.last_window(workspace).map(|window| {
+                let app = window.app_id.as_deref().unwrap_or_default();
+                let title = window.title.as_deref().unwrap_or_default();
+                fl!("last-window", app = app, title = title)
+            }),
+        };
+        let all_windows = self.stacks_icons().then(|| {
+            self.sorted_windows(workspace.id).into_iter().map(|window| {
+                let app = window.app_id.as_deref().unwrap_or_default();
+                let title = window.title.as_deref().unwrap_or_default();
+                fl!("window-entry", app = app, title = title)
+            })
+        });
+        let lines: Vec<String> = switching
+            .into_iter()
+            .chain(shortcut)
+            .chain(windows)
+            .chain(memory)
+            .chain(columns)
+            .chain(last_window)
+            .chain(all_windows.into_iter().flatten())
+            .collect();
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    /// Moves what's kept per workspace to the new ids in `ids` after a reconnect, dropping
+    /// whatever belongs to workspaces that are gone.
+    fn migrate(&mut self, ids: &HashMap<u64, u64>) {
+        let new_id = |id: u64| ids.get(&id).copied();
+        self.scales = self
+            .scales
+            .drain()
+            .filter_map(|(id, scale)| Some((new_id(id)?, scale)))
+            .collect();
+        self.ripples = self
+            .ripples
+            .drain()
+            .filter_map(|(id, ripple)| Some((new_id(id)?, ripple)))
+            .collect();
+        // switches requested before can't be confirmed by the new connection
+        self.pending.clear();
+        self.memory.clear();
+        self.usage.remap(ids);
+        self.history.remap(ids);
+        self.last_activation = self
+            .last_activation
+            .and_then(|(id, time)| Some((new_id(id)?, time)));
+        self.click = self.click.and_then(|(id, time)| Some((new_id(id)?, time)));
+        self.renaming = self
+            .renaming
+            .take()
+            .and_then(|(id, name)| Some((new_id(id)?, name)));
+        if let Some((_, workspace_id)) = &mut self.window_list {
+            // an empty list is shown for a workspace that's gone
+            *workspace_id = new_id(*workspace_id).unwrap_or(*workspace_id);
+        }
+    }
+
+    /// Notifies about `event` on the workspace with `workspace_id` if it's enabled, the
+    /// workspace isn't active and the last notification was long enough ago.
+    fn notify(&mut self, event: WorkspaceEvent, workspace_id: u64, detail: Option<&str>) {
+        if !self.config.notify_on.contains(&event) {
+            return;
+        }
+        let Some(workspace) = self.state.workspace(workspace_id) else {
+            return;
+        };
+        if workspace.is_active {
+            return;
+        }
+        let cooldown = self
+            .config
+            .notify_cooldown_ms
+            .map_or(NOTIFY_COOLDOWN, Duration::from_millis);
+        if self
+            .last_notification
+            .is_some_and(|at| at.elapsed() < cooldown)
+        {
+            return;
+        }
+        let name = match (self.config.label(workspace), &workspace.name) {
+            (Some(label), _) => label.to_owned(),
+            (None, Some(name)) => name.clone(),
+            (None, None) => fl!("workspace", idx = workspace.idx),
+        };
+        let body = match event {
+            WorkspaceEvent::WindowOpened => fl!(
+                "notify-window-opened",
+                app = detail.unwrap_or_default(),
+                workspace = name.as_str()
+            ),
+            WorkspaceEvent::WorkspaceCreated => {
+                fl!("notify-workspace-created", workspace = name.as_str())
+            }
+        };
+        notify::send(&name, &body);
+        self.last_notification = Some(Instant::now());
+    }
+
+    /// Reads the memory used by the windows of the workspace with `workspace_id` in the
+    /// background, counting every process once.
+    fn refresh_memory(&self, workspace_id: u64) -> Task<Message> {
+        let pids: HashSet<i32> = self
+            .state
+            .windows()
+            .filter(|w| w.workspace_id == Some(workspace_id))
+            .filter_map(|w| w.pid)
+            .collect();
+        cosmic::task::future(async move {
+            let bytes = tokio::task::spawn_blocking(move || {
+                let sizes: Vec<u64> = pids.into_iter().filter_map(memory::resident).collect();
+                (!sizes.is_empty()).then(|| sizes.iter().sum())
+            });
+            Message::MemoryRead {
+                workspace_id,
+                bytes: bytes.await.ok().flatten(),
+            }
+        })
+    }
+
+    /// Icon in the corner of the button of `workspace`: a pending switch, the focus lock or the
+    /// layout, in that order. None in presentation mode.
+    fn corner_badge(&self, workspace: &Workspace) -> Option<String> {
+        if self.config.presentation_mode {
+            return None;
+        }
+        if self.is_switching(workspace) {
+            return Some("content-loading-symbolic".to_owned());
+        }
+        if self.focus_locked && workspace.is_active {
+            return Some("changes-prevent-symbolic".to_owned());
+        }
+        self.layout_badge(workspace).map(str::to_owned)
+    }
+
+    /// Number of windows shown on the button of `workspace`, see [`Config::show_window_badges`].
+    fn window_badge(&self, workspace: &Workspace) -> Option<usize> {
+        if !self.config.show_window_badges || self.config.presentation_mode {
+            return None;
+        }
+        let count = self.state.window_count(workspace.id);
+        (count > 0).then_some(count)
+    }
+
+    /// Whether a switch to `workspace` is shown as pending, see [`Config::show_pending`].
+    fn is_switching(&self, workspace: &Workspace) -> bool {
+        self.config.show_pending && self.pending.contains_key(&workspace.id)
+    }
+
+    /// Icon telling whether `workspace` has one or several tiled windows, if enabled.
+    ///
+    /// niri-ipc 25.2 reports no layout modes like tabbed columns, so this is all the layout
+    /// there is to show.
+    fn layout_badge(&self, workspace: &Workspace) -> Option<&str> {
+        if !self.config.show_layout_indicator {
+            return None;
+        }
+        let icons = &self.config.layout_icons;
+        match self.state.tiled_window_count(workspace.id) {
+            0 => None,
+            1 => Some(&icons.single),
+            _ => Some(&icons.multiple),
+        }
+    }
+
+    /// Whether anything on screen is currently animating and needs frame ticks.
+    fn is_animating(&self) -> bool {
+        let neon = self.config.neon_mode
+            && !self.config.reduce_motion
+            && self.state.workspaces().iter().any(|w| w.is_active);
+        let stealth = self.config.stealth_mode && self.stealth_opacity != self.stealth_target();
+        let resizing = self
+            .state
+            .workspaces()
+            .iter()
+            .any(|w| self.scale(w) != self.target_scale(w));
+        let pending = self.config.show_pending && !self.pending.is_empty();
+        neon || stealth || resizing || pending || !self.ripples.is_empty()
+    }
+
+    /// Opacity of the shimmer over the button of `workspace` while its switch is pending.
+    fn shimmer(&self, workspace: &Workspace) -> Option<f32> {
+        if !self.is_switching(workspace) {
+            return None;
+        }
+        if self.config.reduce_motion {
+            return Some(0.15);
+        }
+        // 3 Hz pulse between 0.0 and 0.3
+        let t = self
+            .last_frame
+            .duration_since(self.animation_epoch)
+            .as_secs_f32();
+        Some(0.15 - 0.15 * (3.0 * TAU * t).cos())
+    }
+
+    fn displayed(&self) -> Vec<&Workspace> {
+        displayed(&self.state, &self.config, &self.order, self.panel_output())
+    }
+
+    /// The panel's output, if only its workspaces are shown, see [`Config::show_all_outputs`].
+    ///
+    /// An explicit [`Config::output_filter`] wins, and an output niri doesn't know, or none,
+    /// shows all of them.
+    fn panel_output(&self) -> Option<&str> {
+        let output = self.core.applet.output_name.as_str();
+        let own = !self.config.show_all_outputs
+            && self.config.output_filter.is_empty()
+            && self.state.outputs().contains(&output);
+        own.then_some(output)
+    }
+
+    /// Whether the displayed workspaces or their names changed since they were laid out.
+    fn layout_differs(&self) -> bool {
+        let displayed = self.displayed();
+        displayed.len() != self.laid_out.len()
+            || displayed
+                .iter()
+                .zip(&self.laid_out)
+                .any(|(now, before)| now.id != before.id || now.name != before.name)
+    }
+
+    fn lay_out(&mut self) {
+        self.laid_out = self.displayed().into_iter().cloned().collect();
+        self.layout_changed = None;
+    }
+
+    /// The laid out workspaces with their current state, keeping their laid out names.
+    /// Workspaces that are gone meanwhile are drawn as they were.
+    fn shown(&self) -> Vec<Workspace> {
+        self.laid_out
+            .iter()
+            .map(|before| match self.state.workspace(before.id) {
+                Some(now) => Workspace {
+                    name: before.name.clone(),
+                    ..now.clone()
+                },
+                None => before.clone(),
+            })
+            .collect()
+    }
+
+    fn on_press(&self, workspace: &Workspace) -> Message {
+        let empty = self.state.is_empty(workspace.id);
+        on_press(&self.config, self.modifiers, workspace, empty)
+    }
+
+    /// Whether `id` was just activated from the applet, see [`Config::activation_debounce_ms`].
+    fn is_repeated_activation(&self, id: u64) -> bool {
+        let debounce = self
+            .config
+            .activation_debounce_ms
+            .map_or(ACTIVATION_DEBOUNCE, Duration::from_millis);
+        self.last_activation
+            .is_some_and(|(last, time)| last == id && time.elapsed() < debounce)
+    }
+
+    /// Whether [`Message::ActivateWorkspace`] would send the switch to `id` now. Actions meant
+    /// for the workspace switched to must not go out when it wouldn't.
+    fn can_activate(&self, id: u64) -> bool {
+        !self.focus_locked && !self.muted && !self.is_repeated_activation(id)
+    }
+
+    /// Runs the configured double click action on `workspace_id`.
+    ///
+    /// Empty workspaces are focused and get [`Config::spawn_on_empty`] run on them instead, if
+    /// it's set.
+    fn double_click(&mut self, workspace_id: u64) -> Task<Message> {
+        let empty = self.state.is_empty(workspace_id);
+        if let (true, Some(command)) = (empty, self.config.spawn_on_empty.clone()) {
+            // the command would start on the current workspace instead
+            if !self.can_activate(workspace_id) {
+                return Task::none();
+            }
+            let task = self.update(Message::ActivateWorkspace(workspace_id));
+            // niri handles actions in order, so the command starts on the focused workspace
+            self.send(Action::Spawn {
+                command: vec!["sh".to_owned(), "-c".to_owned(), command],
+            });
+            return task;
+        }
+        match self.config.double_click_action {
+            DoubleClickAction::None => {}
+            DoubleClickAction::MoveWindowHere => self.send(Action::MoveWindowToWorkspace {
+                window_id: None,
+                reference: WorkspaceReferenceArg::Id(workspace_id),
+                focus: true,
+            }),
+            DoubleClickAction::Rename => return self.start_rename(workspace_id),
+        }
+        Task::none()
+    }
+
+    /// Whether the indicators show workspace names, which [`DoubleClickAction::Rename`]
+    /// edits in place.
+    fn shows_names(&self) -> bool {
+        self.config.presentation_mode || self.config.indicator_style == IndicatorStyle::Label
+    }
+
+    fn start_rename(&mut self, workspace_id: u64) -> Task<Message> {
+        let Some(workspace) = self.state.workspace(workspace_id) else {
+            return Task::none();
+        };
+        if !self.shows_names() {
+            return Task::none();
+        }
+        let name = workspace.name.clone().unwrap_or_default();
+        self.renaming = Some((workspace_id, name));
+        widget::text_input::focus(RENAME_INPUT.clone())
+    }
+
+    /// Text field in place of the name of `workspace_id` while it's being renamed.
+    fn rename_input(&self, workspace_id: u64) -> Option<Element<Message>> {
+        let (_, name) = self
+            .renaming
+            .as_ref()
+            .filter(|(renaming, _)| *renaming == workspace_id)?;
+        let input = widget::text_input(fl!("rename-workspace"), name)
+            .id(RENAME_INPUT.clone())
+            .on_input(Message::RenameEdited)
+            .on_submit(|_| Message::CommitRename)
+            .width(Length::Fixed(RENAME_WIDTH));
+        Some(input.into())
+    }
+
+    /// Shows an error in the applet, replacing the previous one.
+    fn report(&mut self, context: String, detail: impl ToString) {
+        let detail = detail.to_string();
+        eprintln!("{context}: {detail}");
+        self.error = Some(ErrorReport {
+            context,
+            detail,
+            time: Instant::now(),
+        });
+    }
+
+    fn send(&mut self, action: Action) {
+        if self.muted {
+            return;
+        }
+        if let Some(sender) = &self.sender {
+            if let Err(e) = sender.send(action) {
+                self.report(fl!("error-action"), e);
+            }
+        }
+    }
+
+    /// Number of open windows, spelled out in the tooltip.
+    fn window_total(&self, position: tooltip::Position) -> Element<Message> {
+        let floating = self.state.windows().filter(|w| w.is_floating).count();
+        let tiled = self.state.windows().count() - floating;
+        let count = match self.config.floating_windows {
+            FloatingWindows::Separate => tiled + floating,
+            FloatingWindows::Exclude => tiled,
+        };
+        let text = widget::text::body(count.to_string());
+        let description = self.window_count(tiled, floating);
+        let text: Element<Message> = match self.config.window_total_scroll {
+            ScrollAction::None => text.into(),
+            _ => mouse_area(text)
+                .on_scroll(Message::ScrolledOverWindowTotal)
+                .into(),
+        };
+        widget::tooltip(text, widget::text::body(description), position).into()
+    }
+
+    /// Runs `scroll` once per full line in `delta`.
+    fn scroll(&mut self, scroll: ScrollAction, delta: ScrollDelta) {
+        let steps = self.scroll_steps(delta);
+        for _ in 0..steps.unsigned_abs() {
+            if let Some(action) = scroll.action(steps > 0) {
+                self.send(action);
+            }
+        }
+    }
+
+    /// Full lines scrolled by `delta`, positive downwards, keeping the rest of touchpad
+    /// scrolling for the next event.
+    fn scroll_steps(&mut self, delta: ScrollDelta) -> i32 {
+        self.scroll_remainder += match delta {
+            ScrollDelta::Lines { y, .. } => y,
+            ScrollDelta::Pixels { y, .. } => y / SCROLL_LINE,
+        };
+        // scrolling up goes back
+        let steps = -self.scroll_remainder.trunc();
+        self.scroll_remainder = self.scroll_remainder.fract();
+        steps as i32
+    }
+
+    /// Describes a window count as configured for floating windows, e.g. "3 windows + 1 floating".
+    fn window_count(&self, tiled: usize, floating: usize) -> String {
+        match (self.config.floating_windows, floating) {
+            (FloatingWindows::Separate, 1..) => fl!(
+                "windows-floating",
+                windows = fl!("windows", count = tiled),
+                floating = floating
+            ),
+            _ => fl!("windows", count = tiled),
+        }
+    }
+
+    /// Icon showing the most recent error in its tooltip, dismissed by clicking it.
+    fn error_indicator(&self, position: tooltip::Position) -> Option<Element<Message>> {
+        let error = self.error.as_ref()?;
+        let text = format!("{}: {}", error.context, error.detail);
+        Some(badge(
+            "dialog-error-symbolic",
+            text,
+            Message::DismissError,
+            position,
+        ))
+    }
+
+    /// Opens the window list of `workspace_id`, or closes it if it's already open.
+    fn toggle_window_list(&mut self, workspace_id: u64) -> Task<Message> {
+        match self.window_list.take() {
+            Some((popup, shown)) if shown == workspace_id => destroy_popup(popup),
+            Some((popup, _)) => {
+                Task::batch([destroy_popup(popup), self.open_window_list(workspace_id)])
+            }
+            None => self.open_window_list(workspace_id),
+        }
+    }
+
+    fn open_window_list(&mut self, workspace_id: u64) -> Task<Message> {
+        let Some(parent) = self.core.main_window_id() else {
+            return Task::none();
+        };
+        let popup = window::Id::unique();
+        self.window_list = Some((popup, workspace_id));
+        self.collapsed_apps.clear();
+        self.label_draft = self
+            .state
+            .workspace(workspace_id)
+            .and_then(|workspace| self.config.label(workspace))
+            .unwrap_or_default()
+            .to_owned();
+        self.name_draft = self
+            .state
+            .workspace(workspace_id)
+            .and_then(|workspace| workspace.name.clone())
+            .unwrap_or_default();
+        let settings = self
+            .core
+            .applet
+            .get_popup_settings(parent, popup, None, None, None);
+        get_popup(settings)
+    }
+
+    /// Names the workspace with id `workspace_id`, or takes its name away if `name` is blank.
+    fn set_name(&mut self, workspace_id: u64, name: &str) {
+        let workspace = Some(WorkspaceReferenceArg::Id(workspace_id));
+        match name.trim() {
+            "" => self.send(Action::UnsetWorkspaceName {
+                reference: workspace,
+            }),
+            name => self.send(Action::SetWorkspaceName {
+                name: name.to_owned(),
+                workspace,
+            }),
+        }
+    }
+
+    /// Opens the empty workspace at the end of the output of `workspace_id` and moves it right
+    /// after that workspace.
+    fn create_workspace_after(&mut self, workspace_id: u64) -> Task<Message> {
+        let Some(workspace) = self.state.workspace(workspace_id) else {
+            return Task::none();
+        };
+        let index = usize::from(workspace.idx) + 1;
+        let output = workspace.output.clone();
+        let empty = self
+            .state
+            .workspaces()
+            .iter()
+            .find(|w| w.output == output && self.state.is_trailing_empty(w))
+            .map(|w| w.id);
+        let Some(id) = empty.filter(|&id| self.can_activate(id)) else {
+            return Task::none();
+        };
+        // niri would remove the empty workspace again once it's moved away unfocused
+        let task = self.update(Message::ActivateWorkspace(id));
+        self.send(Action::MoveWorkspaceToIndex {
+            index,
+            reference: Some(WorkspaceReferenceArg::Id(id)),
+        });
+        task
+    }
+
+    /// Title and windows of the workspace in the pinned window list, kept up to date by niri's
+    /// window events.
+    fn window_list_content(&self, workspace_id: u64) -> Element<Message> {
+        let title = self
+            .state
+            .workspace(workspace_id)
+            .map(Self::accessible_name)
+            .unwrap_or_default();
+        let mut windows: Vec<&Window> = self.state.windows_on(workspace_id).collect();
+        windows.sort_by_key(|w| w.id);
+        let label = widget::text_input(fl!("set-label"), &self.label_draft)
+            .on_input(Message::LabelEdited)
+            .on_submit(|_| Message::SaveLabel(workspace_id));
+        let name = widget::text_input(fl!("rename-workspace"), &self.name_draft)
+            .on_input(Message::NameEdited)
+            .on_submit(|_| Message::SaveName(workspace_id));
+        let mut list = widget::column::with_capacity(windows.len() + 4)
+            .spacing(4)
+            .padding(8)
+            .push(widget::text::heading(title))
+            .push(name)
+            .push(label);
+        if windows.is_empty() {
+            list = list.push(widget::text::body(fl!("windows", count = 0)));
+        }
+        // grouped by app in the order of their first window
+        let mut groups: Vec<(&str, Vec<&Window>)> = Vec::new();
+        for window in windows {
+            let app = window.app_id.as_deref().unwrap_or_default();
+            match groups.iter_mut().find(|(group, _)| *group == app) {
+                Some((_, windows)) => windows.push(window),
+                None => groups.push((app, vec![window])),
+            }
+        }
+        for (app, windows) in groups {
+            let collapsed = self.collapsed_apps.contains(app);
+            list = list.push(self.app_group_header(app, windows.len(), collapsed));
+            if collapsed {
+                continue;
+            }
+            for window in windows {
+                list = list.push(Self::window_row(window));
+            }
+        }
+        if let Some((text, _)) = &self.copied {
+            list = list.push(widget::text::caption(fl!("copied", text = text.as_str())));
+        }
+        list = list.push(
+            widget::button::text(fl!("new-workspace-after"))
+                .on_press(Message::CreateWorkspaceAfter(workspace_id)),
+        );
+        // older versions would reject the action
+        if self
+            .niri_version
+            .map_or(true, |v| v >= FOCUS_PREVIOUS_SINCE)
+        {
+            list = list.push(widget::divider::horizontal::default()).push(
+                widget::button::text(fl!("focus-previous-window"))
+                    .on_press(Message::FocusPreviousWindow),
+            );
+        }
+        list.into()
+    }
+
+    /// Row with the icon, name and window count of `app` in the window list, folding its
+    /// windows in or out when clicked.
+    fn app_group_header(&self, app: &str, count: usize, collapsed: bool) -> Element<Message> {
+        let arrow = match collapsed {
+            true => "pan-end-symbolic",
+            false => "pan-down-symbolic",
+        };
+        let name = match app {
+            "" => fl!("unknown-app"),
+            app => app.to_owned(),
+        };
+        let mut header = widget::row::with_capacity(4)
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .push(widget::icon::from_name(arrow).size(16).icon());
+        if let Some(handle) = self.icons.borrow_mut().get(app) {
+            header = header.push(widget::icon(handle).size(16));
+        }
+        let header = header
+            .push(widget::text::body(name).width(Length::Fill))
+            .push(widget::text::caption(count.to_string()));
+        widget::button::custom(header)
+            .class(cosmic::theme::Button::Text)
+            .width(Length::Fill)
+            .on_press(Message::ToggleAppGroup(app.to_owned()))
+            .into()
+    }
+
+    /// Title of `window` in the window list, focusing it when clicked, with buttons copying its
+    /// app id and title.
+    fn window_row(window: &Window) -> Element<Message> {
+        let app = window.app_id.as_deref().unwrap_or_default();
+        let title = widget::text::body(window.title.as_deref().unwrap_or(app).to_owned());
+        let entry = widget::button::custom(title)
+            .class(cosmic::theme::Button::Text)
+            .width(Length::Fill)
+            .on_press(Message::FocusWindow(window.id));
+        let copy = |icon: &'static str, name: String, text: &Option<String>| {
+            widget::button::custom(widget::icon::from_name(icon).size(16).icon())
+                .class(cosmic::theme::Button::Text)
+                .name(name)
+                .on_press_maybe(text.clone().map(Message::Copy))
+        };
+        widget::row::with_capacity(3)
+            .spacing(4)
+            .padding(Padding::ZERO.left(24))
+            .align_y(Alignment::Center)
+            .push(entry)
+            .push(copy(
+                "edit-copy-symbolic",
+                fl!("copy-app-id"),
+                &window.app_id,
+            ))
+            .push(copy(
+                "insert-text-symbolic",
+                fl!("copy-title"),
+                &window.title,
+            ))
+            .into()
+    }
+
+    /// Cold and hot colour of the heatmap, the defaults for invalid ones.
+    fn heatmap_colors(&self) -> (Color, Color) {
+        let colors = &self.config.heatmap_colors;
+        let defaults = HeatmapColors::default();
+        let parse = |color: &str, default: &str| {
+            style::parse_hex(color)
+                .or_else(|| style::parse_hex(default))
+                .unwrap_or(Color::BLACK)
+        };
+        (
+            parse(&colors.cold, &defaults.cold),
+            parse(&colors.hot, &defaults.hot),
+        )
+    }
+
+    /// Jumps to the workspace with the typed index, preferring one on the panel's output.
+    fn jump_to_typed(&mut self) -> Task<Message> {
+        let Some((digits, _)) = self.number_entry.take() else {
+            return Task::none();
+        };
+        let Ok(idx) = digits.parse::<u8>() else {
+            return Task::none();
+        };
+        let output = self.core.applet.output_name.as_str();
+        let target = self
+            .displayed()
+            .into_iter()
+            .filter(|w| w.idx == idx)
+            .min_by_key(|w| w.output.as_deref() != Some(output))
+            .map(|w| w.id);
+        match target {
+            Some(id) => self.update(Message::ActivateWorkspace(id)),
+            None => Task::none(),
+        }
+    }
+
+    /// Spinner while (re)connecting to niri.
+    fn connection_indicator(&self, position: tooltip::Position) -> Option<Element<Message>> {
+        if self.connection != ConnectionState::Connecting {
+            return None;
+        }
+        let spinner = widget::icon::from_name("process-working-symbolic")
+            .size(16)
+            .icon();
+        Some(widget::tooltip(spinner, widget::text::body(fl!("connecting")), position).into())
+    }
+
+    /// Icon warning that niri's IPC may not be fully understood, dismissed by clicking it.
+    fn version_warning(&self, position: tooltip::Position) -> Option<Element<Message>> {
+        let (niri, supported) = self.version_mismatch.as_ref()?;
+        let text = fl!(
+            "version-mismatch",
+            niri = niri.as_str(),
+            supported = supported.as_str()
+        );
+        Some(badge(
+            "dialog-warning-symbolic",
+            text,
+            Message::DismissVersionWarning,
+            position,
+        ))
+    }
+
+    /// Where to look for niri's socket according to the config.
+    fn discovery(&self) -> niri::Discovery {
+        niri::Discovery {
+            session: self.config.niri_session.clone(),
+            socket_path: self.config.niri_socket.clone(),
+        }
+    }
+
+    /// Placeholder shown instead of workspaces outside of niri, looking again when clicked.
+    fn niri_placeholder(&self, position: tooltip::Position) -> Element<Message> {
+        let button = widget::button::custom(widget::text::body(fl!("niri-not-detected")))
+            .class(cosmic::theme::Button::Text)
+            .on_press(Message::RetryNiri);
+        widget::tooltip(button, widget::text::body(fl!("niri-retry")), position).into()
+    }
+
+    /// Opacity of the buttons when they're faded: by stealth mode, and while the workspaces are
+    /// cached ones or niri is disconnected.
+    fn opacity(&self) -> Option<f32> {
+        let faded = self.config.stealth_mode && self.stealth_opacity < 1.0;
+        let eased =
+            apply_easing(self.stealth_opacity, self.config.animation_easing).clamp(0.0, 1.0);
+        let mut opacity = faded.then_some(eased);
+        if self.stale {
+            opacity = Some(opacity.unwrap_or(1.0) * STALE_OPACITY);
+        } else if let ConnectionState::Disconnected | ConnectionState::Error(_) = self.connection {
+            opacity = Some(opacity.unwrap_or(1.0) * OFFLINE_OPACITY);
+        }
+        opacity
+    }
+
+    /// `class` of a button next to the workspaces, faded along with them.
+    fn faded(&self, class: cosmic::theme::Button) -> cosmic::theme::Button {
+        match self.opacity() {
+            Some(opacity) => style::fade(class, opacity),
+            None => class,
+        }
+    }
+
+    fn stealth_target(&self) -> f32 {
+        match self.pointer_in_applet {
+            true => 1.0,
+            false => 0.0,
+        }
+    }
+
+    /// Current size of the button of `workspace`, eased.
+    fn scale(&self, workspace: &Workspace) -> f32 {
+        if self.config.reduce_motion {
+            return self.target_scale(workspace);
         }
+        let scale = self.scales.get(&workspace.id).copied().unwrap_or(1.0);
+        // a spring may overshoot past full size, but not below nothing
+        apply_easing(scale, self.config.animation_easing).max(0.0)
+    }
+
+    /// Size the button of `workspace` animates towards: full unless zen mode hides it.
+    fn target_scale(&self, workspace: &Workspace) -> f32 {
+        match self.config.zen_mode {
+            true => self.zen_target(workspace),
+            false => 1.0,
+        }
+    }
+
+    /// Whether zen mode shows `workspace`: as the active one or a neighbour by idx.
+    fn zen_target(&self, workspace: &Workspace) -> f32 {
+        let visible = self.state.workspaces().iter().any(|active| {
+            active.is_active
+                && active.output == workspace.output
+                && active.idx.abs_diff(workspace.idx) <= 1
+        });
+        match visible {
+            true => 1.0,
+            false => 0.0,
+        }
+    }
+
+    /// Lets workspaces that appeared grow in from nothing and forgets removed ones.
+    ///
+    /// The first workspaces after startup are shown at their target size right away.
+    fn sync_scales(&mut self) {
+        let startup = self.scales.is_empty();
+        let scales = self
+            .state
+            .workspaces()
+            .iter()
+            .map(|w| {
+                let scale = self.scales.get(&w.id).copied();
+                let initial = match startup {
+                    true => self.target_scale(w),
+                    false => 0.0,
+                };
+                (w.id, scale.unwrap_or(initial))
+            })
+            .collect();
+        self.scales = scales;
     }
 }
 
@@ -33,10 +1417,125 @@ impl WorkspacesApp {
 /// If your application does not need to send messages, you can use an empty enum or `()`.
 #[derive(Debug, Clone)]
 pub enum Message {
-    Ready(mpsc::Sender<u64>),
+    Ready(mpsc::Sender<Action>),
     WorkspaceUpdate(Vec<Workspace>),
-    WorkspaceActivated { id: u64, focused: bool },
+    WorkspaceActivated {
+        id: u64,
+        focused: bool,
+    },
+    WorkspaceActiveWindowChanged {
+        workspace_id: u64,
+        active_window_id: Option<u64>,
+    },
+    WindowsChanged(Vec<Window>),
+    WindowOpenedOrChanged(Window),
+    WindowClosed(u64),
+    WindowFocusChanged(Option<u64>),
     ActivateWorkspace(u64),
+    CloseWindowOnWorkspace(u64),
+    /// A workspace button was clicked while double clicks do something, see [`DOUBLE_CLICK`].
+    WorkspaceClicked(u64),
+    /// Activates the workspace clicked once [`DOUBLE_CLICK`] passed without a second click.
+    ClickTimeout(Instant),
+    /// Runs [`Config::window_total_scroll`].
+    ScrolledOverWindowTotal(ScrollDelta),
+    /// Switches to a workspace further along the strip, unless
+    /// [`Config::disable_scroll_switching`] is set.
+    ScrolledOverWorkspaces(ScrollDelta),
+    /// Locks or unlocks switching workspaces from the applet, so a stray click during a
+    /// presentation doesn't switch away.
+    ToggleFocusLock,
+    /// Stops or resumes sending requests to niri, the display keeps following it.
+    ToggleMute,
+    /// Power saving was turned on or off, see [`Config::monochrome_when_power_saving`].
+    PowerSavingChanged(bool),
+    /// Goes back or forward through the focused workspaces, see [`Config::show_history_buttons`].
+    HistoryBack,
+    HistoryForward,
+    /// Switches presentation mode and saves it, see [`Config::presentation_mode`].
+    TogglePresentation,
+    /// Moves the window with this id between the floating and the tiling layout.
+    ToggleFloating(u64),
+    /// Runs the action of the action button at this index.
+    RunAction(usize),
+    ModifiersChanged(Modifiers),
+    ConfigChanged(Config),
+    Tick(Instant),
+    PointerEntered,
+    PointerExited,
+    /// The pointer moved to this position on the button of the workspace with this id.
+    PointerMoved(u64, Point),
+    /// Pins the window list of a workspace open, or closes it again.
+    ToggleWindowList(u64),
+    CloseWindowList,
+    /// Focuses the window with this id from the window list, closing it.
+    FocusWindow(u64),
+    FocusPreviousWindow,
+    PopupClosed(window::Id),
+    LabelEdited(String),
+    /// Updates the niri name typed into the pinned window list.
+    NameEdited(String),
+    /// Gives the workspace with this id the typed niri name, or takes its name away if it's
+    /// blank.
+    SaveName(u64),
+    /// Adds a workspace right after the one with this id, closing the window list.
+    CreateWorkspaceAfter(u64),
+    /// Updates the name typed while renaming in the panel, see [`DoubleClickAction::Rename`].
+    RenameEdited(String),
+    /// Gives the workspace the typed name, or takes its name away if it's blank.
+    CommitRename,
+    CancelRename,
+    /// Saves the typed label for the workspace with this id, or removes it if empty.
+    SaveLabel(u64),
+    /// Opens or closes the new workspace menu.
+    ToggleTemplateMenu,
+    /// Sets up a new workspace, from the template at this index in the config if given.
+    CreateWorkspace(Option<usize>),
+    Error {
+        context: String,
+        detail: String,
+    },
+    DismissError,
+    /// niri reported a different version than the one supported by niri-ipc.
+    VersionMismatch {
+        niri: String,
+        supported: String,
+    },
+    DismissVersionWarning,
+    /// niri's socket can't be found, it's looked for again periodically.
+    NiriMissing,
+    /// Looks for niri's socket again without waiting for the next poll.
+    RetryNiri,
+    ConnectionStateChanged(ConnectionState),
+    /// A digit of a workspace number was typed, see [`NUMBER_ENTRY_TIMEOUT`].
+    DigitTyped(char),
+    CommitNumberEntry,
+    /// Jumps to the typed workspace once no digit was typed for [`NUMBER_ENTRY_TIMEOUT`].
+    ExpireNumberEntry(Instant),
+    /// Folds the windows of the app with this id in or out of the window list.
+    ToggleAppGroup(String),
+    /// Copies an app id or title from the window list to the clipboard.
+    Copy(String),
+    /// Looks up the app icons asked for while drawing.
+    LoadIcons,
+    /// Icon paths found for app ids in the icon theme `theme`.
+    IconsLoaded {
+        theme: String,
+        found: Vec<(String, Option<PathBuf>)>,
+    },
+    /// Memory used by the windows of a workspace, if any of it could be read.
+    MemoryRead {
+        workspace_id: u64,
+        bytes: Option<u64>,
+    },
+    /// Lays out the displayed workspaces once they differed for [`LAYOUT_DEBOUNCE`].
+    SettleLayout(Instant),
+    /// Caches the workspaces once they stayed unchanged for [`SNAPSHOT_DELAY`].
+    SaveSnapshot(Instant),
+    /// Hides the copy confirmation once it's older than [`COPIED_TIMEOUT`].
+    ExpireCopied(Instant),
+    /// Clears the error once it's older than [`ERROR_TIMEOUT`].
+    ExpireError(Instant),
 }
 
 /// Implement the `Application` trait for your application.
@@ -72,14 +1571,28 @@ impl Application for WorkspacesApp {
     /// - `flags` is used to pass in any data that your application needs to use before it starts.
     /// - `Command` type is used to send messages to your application. `Command::none()` can be used to send no messages to your application.
     fn init(core: Core, _flags: Self::Flags) -> (Self, Task<Self::Message>) {
-        let app = WorkspacesApp::new(core);
+        let (handler, config, errors) = Config::load(Self::APP_ID);
+        let config = config.for_output(&core.applet.output_name);
+        let mut app = WorkspacesApp::new(core, config);
+        app.config_handler = handler;
+        app.snapshot_cache = SnapshotCache::new(&app.core.applet.output_name);
+        let cached = app.snapshot_cache.load();
+        if !cached.is_empty() {
+            app.state.set_workspaces(cached);
+            app.sync_scales();
+            app.lay_out();
+            app.stale = true;
+        }
+        if !errors.is_empty() {
+            app.report(fl!("error-config"), errors.join("\n"));
+        }
 
         (app, Task::none())
     }
 
-    // fn on_close_requested(&self, id: Id) -> Option<Message> {
-    //     Some(Message::PopupClosed(id))
-    // }
+    fn on_close_requested(&self, id: window::Id) -> Option<Message> {
+        Some(Message::PopupClosed(id))
+    }
 
     /// This is the main view of your application, it is the root of your widget tree.
     ///
@@ -92,85 +1605,1578 @@ impl Application for WorkspacesApp {
             self.core.applet.anchor,
             PanelAnchor::Top | PanelAnchor::Bottom
         );
-        let mut children: Vec<Element<Message>> = Vec::with_capacity(self.workspaces.len());
-        for workspace in &self.workspaces {
-            let class = match workspace.is_active {
-                true => cosmic::style::Button::Suggested,
-                false => cosmic::style::Button::Standard,
-            };
-            let height = if horizontal {
-                Length::Fixed(self.core.applet.suggested_size(false).1 as f32)
-            } else {
-                Length::Fixed(16.0)
-            };
-            let width = if !horizontal {
-                Length::Fixed(self.core.applet.suggested_size(false).1 as f32)
-            } else {
-                Length::Fixed(16.0)
+        let opacity = self.opacity();
+        // the suggested size can be unusable on extremely small or large panels
+        let thickness = (self.core.applet.suggested_size(false).1 as f32)
+            .min(self.config.max_button_thickness.unwrap_or(MAX_THICKNESS))
+            .max(self.config.min_button_thickness.unwrap_or(MIN_THICKNESS));
+        let indicators = self.indicators();
+        let spacing = self.config.button_spacing.unwrap_or(SPACING);
+        let wrap = self.config.wrap_layout
+            && self.laid_out.len() > self.config.wrap_threshold
+            && thickness >= 2.0 * MIN_LINE_THICKNESS + spacing;
+        let lines: usize = match wrap {
+            true => 2,
+            false => 1,
+        };
+        let zoom = match self.config.presentation_mode {
+            true => PRESENTATION_SCALE,
+            false => 1.0,
+        };
+        let metrics = Metrics {
+            horizontal,
+            thickness: zoom * (thickness - spacing * (lines - 1) as f32) / lines as f32,
+            length: zoom * self.config.button_length.unwrap_or(BUTTON_LENGTH),
+            glow: self.config.neon_mode.then_some(self.active_glow_strength),
+            opacity,
+            monochrome: self.config.monochrome_mode
+                || (self.config.monochrome_when_power_saving && self.power_saving),
+            flat: self.config.flat_mode,
+            segmented: self.config.segmented,
+            presentation: self.config.presentation_mode,
+            heatmap: self.config.heatmap.then(|| self.heatmap_colors()),
+            tooltip_position: match self.core.applet.anchor {
+                PanelAnchor::Top => tooltip::Position::Bottom,
+                PanelAnchor::Bottom => tooltip::Position::Top,
+                PanelAnchor::Left => tooltip::Position::Right,
+                PanelAnchor::Right => tooltip::Position::Left,
+            },
+            press_activates: self.config.activate_on_press,
+            shape: self.config.button_shape,
+        };
+        let style = match self.config.presentation_mode {
+            true => IndicatorStyle::Label,
+            false => self.config.indicator_style,
+        };
+        // workspaces of several outputs are told apart by a separator, which the dividers of
+        // the segmented control already are
+        let separated = !self.config.segmented && self.panel_output().is_none();
+        let mut previous_output = None;
+        let shown = indicators.len();
+        let mut buttons: Vec<Element<Message>> = Vec::with_capacity(indicators.len());
+        for indicator in indicators {
+            let id = indicator.id;
+            let output = self
+                .laid_out
+                .iter()
+                .find(|w| w.id == id)
+                .and_then(|w| w.output.as_deref());
+            if separated && previous_output.is_some_and(|previous| previous != output) {
+                buttons.push(render::separator(&metrics));
+            }
+            previous_output = Some(output);
+            let button = match self.rename_input(id) {
+                Some(input) => input,
+                None => style.render(indicator, &metrics),
             };
-            children.push(
-                widget::button::custom(cosmic::widget::Space::new(width, height))
-                    .class(class)
-                    .on_press(Message::ActivateWorkspace(workspace.id))
+            buttons.push(
+                mouse_area(button)
+                    .on_right_press(Message::ToggleWindowList(id))
+                    .on_move(move |position| Message::PointerMoved(id, position))
                     .into(),
-            )
+            );
         }
-        let container: Element<Message> = if !horizontal {
-            widget::Column::with_children(children)
-                .spacing(4)
-                .padding(8)
-                .into()
-        } else {
-            widget::Row::with_children(children)
-                .spacing(4)
-                .padding(8)
-                .into()
+        let reserved = match self.config.sizing {
+            Sizing::Slots(slots) => slots,
+            Sizing::Stable => self.most_shown,
+            _ => 0,
         };
-        self.core.applet.autosize_window(container).into()
+        let placeholders = reserved.saturating_sub(shown);
+        let mut children = match self.config.segmented {
+            true => vec![segmented(buttons, &metrics)],
+            false => buttons,
+        };
+        children.extend((0..placeholders).map(|_| render::placeholder(&metrics)));
+        if self.config.show_history_buttons {
+            let [back, forward] = self.history_buttons();
+            children.splice(0..0, [back, forward]);
+        }
+        if self.config.show_focus_lock {
+            children.push(self.focus_lock_button());
+        }
+        if self.config.show_mute_toggle {
+            children.push(self.mute_toggle());
+        }
+        children.extend(self.action_buttons());
+        if !self.config.workspace_templates.is_empty() {
+            children.push(self.new_workspace_button());
+        }
+        if self.config.show_presentation_toggle {
+            children.push(self.presentation_toggle());
+        }
+        if self.config.show_floating_toggle {
+            children.push(self.floating_toggle());
+        }
+        if self.config.show_window_total && !self.config.presentation_mode {
+            let total = self.window_total(metrics.tooltip_position);
+            match self.config.window_total_position {
+                Placement::Start => children.insert(0, total),
+                Placement::End => children.push(total),
+            }
+        }
+        if let Some((digits, _)) = &self.number_entry {
+            children.push(widget::text::body(digits.clone()).into());
+        }
+        if self.niri_missing {
+            children.push(self.niri_placeholder(metrics.tooltip_position));
+        }
+        children.extend(self.connection_indicator(metrics.tooltip_position));
+        children.extend(self.version_warning(metrics.tooltip_position));
+        children.extend(self.error_indicator(metrics.tooltip_position));
+        let per_line = children.len().div_ceil(lines).max(1);
+        let mut children = children.into_iter();
+        let lines: Vec<Element<Message>> = (0..lines)
+            .map(|_| {
+                let line_children = children.by_ref().take(per_line).collect();
+                line(line_children, horizontal, spacing)
+            })
+            .collect();
+        let container = match lines.len() {
+            1 => lines.into_iter().next().unwrap(),
+            _ => line(lines, !horizontal, spacing),
+        };
+        // for the text outside of buttons, like the window total
+        let container: Element<Message> = match opacity {
+            Some(opacity) => widget::container(container)
+                .class(style::faded_text(opacity))
+                .into(),
+            None => container,
+        };
+        let container: Element<Message> = match &self.config.background {
+            Some(background) => {
+                let color = background.color.as_deref().and_then(style::parse_hex);
+                widget::container(container)
+                    .padding(background.padding)
+                    .class(style::background(
+                        color,
+                        background.opacity * opacity.unwrap_or(1.0),
+                        background.radius,
+                    ))
+                    .into()
+            }
+            None => container,
+        };
+        let padding = self.config.padding.unwrap_or(PADDING);
+        let container = widget::container(container).padding(padding);
+        // the window still follows its content, which just doesn't change size anymore
+        let container = match (self.config.sizing, horizontal) {
+            (Sizing::Auto | Sizing::Slots(_) | Sizing::Stable, _) => container,
+            (Sizing::Clamped(max), true) => container.max_width(max).clip(true),
+            (Sizing::Clamped(max), false) => container.max_height(max).clip(true),
+            (Sizing::Fixed(length), true) => container
+                .width(Length::Fixed(length))
+                .align_x(Alignment::Center)
+                .clip(true),
+            (Sizing::Fixed(length), false) => container
+                .height(Length::Fixed(length))
+                .align_y(Alignment::Center)
+                .clip(true),
+        };
+        let mut container = mouse_area(container)
+            .on_enter(Message::PointerEntered)
+            .on_exit(Message::PointerExited);
+        if !self.config.disable_scroll_switching {
+            container = container.on_scroll(Message::ScrolledOverWorkspaces);
+        }
+        self.core.applet.autosize_window(container).into()
+    }
+
+    fn view_window(&self, id: window::Id) -> Element<Self::Message> {
+        match self.window_list {
+            Some((popup, workspace_id)) if popup == id => self
+                .core
+                .applet
+                .popup_container(self.window_list_content(workspace_id))
+                .into(),
+            _ if self.template_menu == Some(id) => self
+                .core
+                .applet
+                .popup_container(self.template_menu_content())
+                .into(),
+            _ => widget::Space::new(0, 0).into(),
+        }
     }
 
     /// Application messages are handled here. The application state can be modified based on
     /// what message was received. Commands may be returned for asynchronous execution on a
     /// background thread managed by the application's executor.
     fn update(&mut self, message: Self::Message) -> Task<Self::Message> {
+        // clicking another workspace ends renaming one
+        if let Message::WorkspaceClicked(_)
+        | Message::ActivateWorkspace(_)
+        | Message::ToggleWindowList(_) = message
+        {
+            self.renaming = None;
+        }
         match message {
-            Message::WorkspaceUpdate(mut workspaces) => {
-                workspaces.sort_unstable_by_key(|a| a.id);
-                self.workspaces = workspaces;
+            Message::WorkspaceUpdate(workspaces) => {
+                self.niri_missing = false;
+                let reconnected = std::mem::take(&mut self.reconnected);
+                // the cached workspaces weren't really there
+                let stale = std::mem::take(&mut self.stale);
+                let old = match stale {
+                    true => Vec::new(),
+                    false => self.state.workspaces().to_vec(),
+                };
+                self.state.set_workspaces(workspaces);
+                if reconnected {
+                    self.migrate(&state::matching_ids(&old, self.state.workspaces()));
+                } else {
+                    // the first snapshot of a connection only lists what's there already
+                    let created: Vec<u64> = self
+                        .state
+                        .workspaces()
+                        .iter()
+                        .map(|w| w.id)
+                        .filter(|id| old.iter().all(|w| w.id != *id))
+                        .collect();
+                    for id in created {
+                        self.notify(WorkspaceEvent::WorkspaceCreated, id, None);
+                    }
+                }
+                let focused = self.state.focused().map(|w| w.id);
+                self.usage.focus(focused, Instant::now());
+                let mut workspaces: Vec<&Workspace> = self.state.workspaces().iter().collect();
+                self.order.sort(&mut workspaces);
+                self.order.remember(&workspaces);
+                self.sync_scales();
+                let shown = self.displayed().len();
+                self.most_shown = self.most_shown.max(shown);
+                // the first workspaces are shown right away
+                if self.laid_out.is_empty() || stale {
+                    self.lay_out();
+                }
+                self.unsaved_since.get_or_insert_with(Instant::now);
+                // nothing left to rename
+                if let Some((id, _)) = self.renaming {
+                    if self.state.workspace(id).is_none() {
+                        self.renaming = None;
+                    }
+                }
+                return self.load_icons();
             }
             Message::WorkspaceActivated { id, focused } => {
-                for workspace in self.workspaces.iter_mut() {
-                    if workspace.id == id {
-                        workspace.is_active = true;
-                        workspace.is_focused = focused;
-                    } else {
-                        workspace.is_active = false;
-                        workspace.is_focused = false;
+                let requested = self.pending.remove(&id).is_some();
+                self.state.activated(id, focused);
+                self.unsaved_since.get_or_insert_with(Instant::now);
+                if focused && self.state.workspace(id).is_some() {
+                    self.history.visited(id);
+                }
+                let focused = self.state.focused().map(|w| w.id);
+                self.usage.focus(focused, Instant::now());
+                // only switches the applet asked for and niri confirmed
+                if let (true, Some(command)) = (requested, &self.config.activation_command) {
+                    if let Some(workspace) = self.state.workspace(id) {
+                        hook::run(command, workspace);
                     }
                 }
             }
-            Message::ActivateWorkspace(id) => {
-                for workspace in self.workspaces.iter_mut() {
-                    if workspace.id == id {
-                        workspace.is_active = true;
-                    } else {
-                        workspace.is_active = false;
+            Message::WorkspaceClicked(_) if self.focus_locked => {}
+            Message::WorkspaceClicked(id) => match self.click.take() {
+                Some((clicked, time)) if clicked == id && time.elapsed() < DOUBLE_CLICK => {
+                    return self.double_click(id);
+                }
+                previous => {
+                    self.click = Some((id, Instant::now()));
+                    // a click on another workspace doesn't wait any longer
+                    if let Some((clicked, _)) = previous {
+                        return self.update(Message::ActivateWorkspace(clicked));
                     }
                 }
+            },
+            Message::ClickTimeout(now) => {
+                let expired = self
+                    .click
+                    .is_some_and(|(_, time)| now.saturating_duration_since(time) >= DOUBLE_CLICK);
+                if let (true, Some((id, _))) = (expired, self.click.take()) {
+                    return self.update(Message::ActivateWorkspace(id));
+                }
+            }
+            Message::ActivateWorkspace(_) if self.focus_locked => {}
+            // muting also keeps the applet from switching, not just from sending actions
+            Message::ActivateWorkspace(_) if self.muted => {}
+            Message::ActivateWorkspace(id) if self.is_repeated_activation(id) => {}
+            Message::ActivateWorkspace(id) => {
+                self.last_activation = Some((id, Instant::now()));
+                // only presses ripple, from where the button was pressed
+                let pressed = self.pointer.filter(|(hovered, _)| *hovered == id);
+                if let (Some((_, center)), false) = (pressed, self.config.reduce_motion) {
+                    let ripple = RippleState {
+                        center,
+                        radius: 0.0,
+                        alpha: 1.0,
+                    };
+                    self.ripples.insert(id, ripple);
+                }
                 if let Some(sender) = &self.sender {
-                    sender.send(id).unwrap();
+                    match self.state.request_activation(id, sender) {
+                        Ok(()) => {
+                            self.pending.insert(id, Instant::now());
+                        }
+                        Err(e) => self.report(fl!("error-action"), e),
+                    }
                 }
             }
+            Message::WorkspaceActiveWindowChanged {
+                workspace_id,
+                active_window_id,
+            } => self
+                .state
+                .active_window_changed(workspace_id, active_window_id),
+            Message::WindowsChanged(windows) => {
+                self.state.set_windows(windows);
+                return self.load_icons();
+            }
+            Message::WindowOpenedOrChanged(window) => {
+                let opened = self.state.window(window.id).is_none();
+                let app = window.app_id.clone();
+                let workspace_id = window.workspace_id;
+                self.state.window_opened_or_changed(window);
+                if let (true, Some(workspace_id)) = (opened, workspace_id) {
+                    let event = WorkspaceEvent::WindowOpened;
+                    self.notify(event, workspace_id, app.as_deref());
+                }
+                return self.load_icons();
+            }
+            Message::WindowClosed(id) => self.state.window_closed(id),
+            Message::WindowFocusChanged(id) => self.state.window_focus_changed(id),
+            Message::CloseWindowOnWorkspace(workspace_id) => {
+                if let Some(id) = self.state.active_window(workspace_id) {
+                    self.send(Action::CloseWindow { id: Some(id) });
+                }
+            }
+            Message::ModifiersChanged(modifiers) => self.modifiers = modifiers,
             Message::Ready(sender) => self.sender = Some(sender),
+            Message::Error { context, detail } => self.report(context, detail),
+            Message::DismissError => self.error = None,
+            Message::VersionMismatch { niri, supported } => {
+                self.niri_version = niri::parse_version(&niri);
+                self.version_mismatch = Some((niri, supported));
+            }
+            Message::DismissVersionWarning => self.version_mismatch = None,
+            Message::NiriMissing => self.niri_missing = true,
+            Message::RetryNiri => {
+                self.niri_missing = false;
+                self.retries = self.retries.wrapping_add(1);
+            }
+            Message::ConnectionStateChanged(state) => {
+                if let ConnectionState::Error(detail) = &state {
+                    self.report(fl!("error-connect"), detail.clone());
+                }
+                if state == ConnectionState::Connected {
+                    self.reconnected = true;
+                }
+                self.connection = state;
+            }
+            Message::DigitTyped(digit) => {
+                let (digits, typed) = self
+                    .number_entry
+                    .get_or_insert_with(|| (String::new(), Instant::now()));
+                digits.push(digit);
+                *typed = Instant::now();
+                if digits.len() >= NUMBER_ENTRY_DIGITS {
+                    return self.jump_to_typed();
+                }
+            }
+            Message::ToggleAppGroup(app) => {
+                if !self.collapsed_apps.remove(&app) {
+                    self.collapsed_apps.insert(app);
+                }
+            }
+            Message::Copy(text) => {
+                self.copied = Some((text.clone(), Instant::now()));
+                return clipboard::write(text);
+            }
+            Message::LoadIcons => return self.load_icons(),
+            Message::IconsLoaded { theme, found } => self.icons.get_mut().loaded(&theme, found),
+            Message::MemoryRead {
+                workspace_id,
+                bytes: Some(bytes),
+            } => {
+                self.memory.insert(workspace_id, bytes);
+            }
+            Message::MemoryRead { workspace_id, .. } => {
+                self.memory.remove(&workspace_id);
+            }
+            Message::SettleLayout(now) => match self.layout_changed {
+                _ if !self.layout_differs() => self.layout_changed = None,
+                None if !self.laid_out.is_empty() => self.layout_changed = Some(now),
+                Some(since) if now.saturating_duration_since(since) < LAYOUT_DEBOUNCE => {}
+                _ => self.lay_out(),
+            },
+            Message::SaveSnapshot(now) => {
+                let settled = self
+                    .unsaved_since
+                    .is_some_and(|since| now.saturating_duration_since(since) >= SNAPSHOT_DELAY);
+                if settled {
+                    self.unsaved_since = None;
+                    if let Err(e) = self.snapshot_cache.save(self.state.workspaces()) {
+                        eprintln!("failed to cache workspaces: {e}");
+                    }
+                }
+            }
+            Message::ExpireCopied(now) => {
+                let expired = self.copied.as_ref().is_some_and(|(_, copied)| {
+                    now.saturating_duration_since(*copied) >= COPIED_TIMEOUT
+                });
+                if expired {
+                    self.copied = None;
+                }
+            }
+            Message::CommitNumberEntry => return self.jump_to_typed(),
+            Message::ExpireNumberEntry(now) => {
+                let expired = self.number_entry.as_ref().is_some_and(|(_, typed)| {
+                    now.saturating_duration_since(*typed) >= NUMBER_ENTRY_TIMEOUT
+                });
+                if expired {
+                    return self.jump_to_typed();
+                }
+            }
+            Message::ExpireError(now) => {
+                let expired = self
+                    .error
+                    .as_ref()
+                    .is_some_and(|e| now.saturating_duration_since(e.time) >= ERROR_TIMEOUT);
+                if expired {
+                    self.error = None;
+                }
+            }
+            Message::ConfigChanged(mut config) => {
+                let errors = config.validate();
+                if !errors.is_empty() {
+                    self.report(fl!("error-config"), errors.join("\n"));
+                }
+                if config.niri_config != self.config.niri_config {
+                    self.shortcuts = Self::load_shortcuts(&config);
+                }
+                if config.action_buttons != self.config.action_buttons {
+                    self.actions = Self::parse_actions(&config);
+                }
+                // nothing keeps them up to date anymore
+                if !config.events.windows {
+                    self.state.forget_windows();
+                }
+                self.config = config.for_output(&self.core.applet.output_name);
+            }
+            Message::ToggleFocusLock => self.focus_locked = !self.focus_locked,
+            Message::ToggleMute => self.muted = !self.muted,
+            Message::PowerSavingChanged(saving) => self.power_saving = saving,
+            Message::HistoryBack | Message::HistoryForward => {
+                let state = &self.state;
+                let exists = |id| state.workspace(id).is_some();
+                let id = match message {
+                    Message::HistoryBack => self.history.back(exists),
+                    _ => self.history.forward(exists),
+                };
+                if let Some(id) = id {
+                    return self.update(Message::ActivateWorkspace(id));
+                }
+            }
+            Message::ScrolledOverWindowTotal(delta) => {
+                self.scroll(self.config.window_total_scroll, delta);
+            }
+            Message::ScrolledOverWorkspaces(delta) => {
+                let steps = self.scroll_steps(delta);
+                let stop = self.config.scroll_stops_at_ends;
+                if let Some(id) = scroll_target(&self.displayed(), steps, stop) {
+                    return self.update(Message::ActivateWorkspace(id));
+                }
+            }
+            Message::TogglePresentation => {
+                let enabled = !self.config.presentation_mode;
+                let result = match &self.config_handler {
+                    Some(handler) => self.config.set_presentation_mode(handler, enabled),
+                    None => {
+                        self.config.presentation_mode = enabled;
+                        Ok(true)
+                    }
+                };
+                if let Err(e) = result {
+                    self.report(fl!("error-config"), e);
+                }
+            }
+            Message::RunAction(i) => {
+                if let Some((_, action)) = self.actions.get(i).cloned() {
+                    self.send(action);
+                }
+            }
+            Message::Tick(now) => {
+                // 1 Hz sine wave between 0.6 and 1.0, steady without motion
+                let t = now.duration_since(self.animation_epoch).as_secs_f32();
+                self.active_glow_strength = match self.config.reduce_motion {
+                    true => 1.0,
+                    false => 0.8 + 0.2 * (TAU * t).sin(),
+                };
+
+                // clamped so the first frame after an idle period doesn't skip the fade
+                let dt = now
+                    .saturating_duration_since(self.last_frame)
+                    .min(FRAME_INTERVAL * 2);
+                let step = dt.as_secs_f32() / STEALTH_FADE.as_secs_f32();
+                self.stealth_opacity = approach(self.stealth_opacity, self.stealth_target(), step);
+
+                let step = dt.as_secs_f32() / RESIZE.as_secs_f32();
+                let targets: Vec<(u64, f32)> = self
+                    .state
+                    .workspaces()
+                    .iter()
+                    .map(|w| (w.id, self.target_scale(w)))
+                    .collect();
+                for (id, target) in targets {
+                    if let Some(scale) = self.scales.get_mut(&id) {
+                        *scale = approach(*scale, target, step);
+                    }
+                }
+                let step = dt.as_secs_f32() / RIPPLE.as_secs_f32();
+                for ripple in self.ripples.values_mut() {
+                    ripple.alpha = (ripple.alpha - step).max(0.0);
+                    let progress = apply_easing(1.0 - ripple.alpha, self.config.animation_easing);
+                    ripple.radius = progress.max(0.0);
+                }
+                self.ripples.retain(|_, ripple| ripple.alpha > 0.0);
+                self.pending
+                    .retain(|_, start| now.saturating_duration_since(*start) < PENDING_TIMEOUT);
+                self.last_frame = now;
+            }
+            Message::PointerEntered => self.pointer_in_applet = true,
+            Message::PointerExited => {
+                self.pointer_in_applet = false;
+                self.pointer = None;
+            }
+            Message::PointerMoved(id, position) => {
+                let entered = self.pointer.map_or(true, |(hovered, _)| hovered != id);
+                self.pointer = Some((id, position));
+                // read once the pointer gets onto the button, as its tooltip is about to open
+                if entered && self.config.show_resource_usage {
+                    return self.refresh_memory(id);
+                }
+            }
+            Message::ToggleWindowList(workspace_id) => {
+                return self.toggle_window_list(workspace_id)
+            }
+            Message::ToggleFloating(id) => {
+                self.send(Action::ToggleWindowFloating { id: Some(id) });
+            }
+            Message::FocusWindow(id) => {
+                self.send(Action::FocusWindow { id });
+                if let Some((popup, _)) = self.window_list.take() {
+                    return destroy_popup(popup);
+                }
+            }
+            Message::FocusPreviousWindow => {
+                self.send(Action::FocusWindowPrevious {});
+                if let Some((popup, _)) = self.window_list.take() {
+                    return destroy_popup(popup);
+                }
+            }
+            Message::CloseWindowList => {
+                if let Some((popup, _)) = self.window_list.take() {
+                    return destroy_popup(popup);
+                }
+            }
+            Message::PopupClosed(id) => {
+                if self.window_list.is_some_and(|(popup, _)| popup == id) {
+                    self.window_list = None;
+                }
+                if self.template_menu == Some(id) {
+                    self.template_menu = None;
+                }
+            }
+            Message::LabelEdited(label) => self.label_draft = label,
+            Message::NameEdited(name) => self.name_draft = name,
+            Message::SaveName(id) => {
+                let name = self.name_draft.clone();
+                self.set_name(id, &name);
+            }
+            Message::CreateWorkspaceAfter(id) => {
+                let task = self.create_workspace_after(id);
+                return match self.window_list.take() {
+                    Some((popup, _)) => Task::batch([task, destroy_popup(popup)]),
+                    None => task,
+                };
+            }
+            Message::RenameEdited(name) => {
+                if let Some((_, typed)) = &mut self.renaming {
+                    *typed = name;
+                }
+            }
+            Message::CommitRename => {
+                let Some((id, name)) = self.renaming.take() else {
+                    return Task::none();
+                };
+                self.set_name(id, &name);
+            }
+            Message::CancelRename => self.renaming = None,
+            Message::SaveLabel(workspace_id) => {
+                let Some(workspace) = self.state.workspace(workspace_id) else {
+                    return Task::none();
+                };
+                let mut labels = self.config.labels.clone();
+                let key = Config::label_key(workspace);
+                match self.label_draft.trim() {
+                    "" => labels.remove(&key),
+                    label => labels.insert(key, label.to_owned()),
+                };
+                let result = match &self.config_handler {
+                    Some(handler) => self.config.set_labels(handler, labels),
+                    None => {
+                        self.config.labels = labels;
+                        Ok(true)
+                    }
+                };
+                if let Err(e) = result {
+                    self.report(fl!("error-config"), e);
+                }
+            }
+            Message::ToggleTemplateMenu => return self.toggle_template_menu(),
+            Message::CreateWorkspace(template) => {
+                let task = self.create_workspace(template);
+                return match self.template_menu.take() {
+                    Some(popup) => Task::batch([task, destroy_popup(popup)]),
+                    None => task,
+                };
+            }
         }
         Task::none()
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
-        Subscription::run(niri::sub)
+        let mut subscriptions = vec![
+            Subscription::run_with_id(
+                (
+                    "niri",
+                    self.config.output_filter.clone(),
+                    self.discovery(),
+                    self.config.events,
+                    self.retries,
+                ),
+                niri::sub(
+                    self.config.output_filter.clone(),
+                    self.discovery(),
+                    self.config.events,
+                ),
+            ),
+            self.core
+                .watch_config::<Config>(Self::APP_ID)
+                .map(|update| Message::ConfigChanged(update.config)),
+        ];
+        if self.config.monochrome_when_power_saving {
+            subscriptions.push(Subscription::run_with_id("power-profile", power::sub()));
+        }
+        if self.config.alt_click_closes_window {
+            subscriptions.push(event::listen_with(|event, _, _| match event {
+                cosmic::iced::Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                    Some(Message::ModifiersChanged(modifiers))
+                }
+                _ => None,
+            }));
+        }
+        // only delivered while the applet has keyboard focus, and not while typing in a text field
+        subscriptions.push(event::listen_with(|event, status, _| match event {
+            _ if status == event::Status::Captured => None,
+            cosmic::iced::Event::Keyboard(keyboard::Event::KeyPressed {
+                key: Key::Named(Named::Enter),
+                ..
+            }) => Some(Message::CommitNumberEntry),
+            cosmic::iced::Event::Keyboard(keyboard::Event::KeyPressed {
+                text: Some(text), ..
+            }) => {
+                let mut chars = text.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(digit), None) if digit.is_ascii_digit() => {
+                        Some(Message::DigitTyped(digit))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }));
+        if self.click.is_some() {
+            subscriptions.push(time::every(DOUBLE_CLICK / 8).map(Message::ClickTimeout));
+        }
+        if self.number_entry.is_some() {
+            subscriptions
+                .push(time::every(NUMBER_ENTRY_TIMEOUT / 4).map(Message::ExpireNumberEntry));
+        }
+        // icons asked for by `view()` outside of the updates loading them
+        if self.icons.borrow().has_missing() {
+            subscriptions.push(time::every(FRAME_INTERVAL).map(|_| Message::LoadIcons));
+        }
+        if self.layout_differs() {
+            subscriptions.push(time::every(LAYOUT_DEBOUNCE / 4).map(Message::SettleLayout));
+        }
+        if self.unsaved_since.is_some() {
+            subscriptions.push(time::every(SNAPSHOT_DELAY / 4).map(Message::SaveSnapshot));
+        }
+        if self.copied.is_some() {
+            subscriptions.push(time::every(COPIED_TIMEOUT / 4).map(Message::ExpireCopied));
+        }
+        if self.renaming.is_some() {
+            subscriptions.push(event::listen_with(|event, status, _| match event {
+                cosmic::iced::Event::Keyboard(keyboard::Event::KeyPressed {
+                    key: Key::Named(Named::Escape),
+                    ..
+                }) => Some(Message::CancelRename),
+                // clicks next to the text field and anywhere outside the applet
+                cosmic::iced::Event::Mouse(mouse::Event::ButtonPressed(_))
+                    if status == event::Status::Ignored =>
+                {
+                    Some(Message::CancelRename)
+                }
+                cosmic::iced::Event::Window(window::Event::Unfocused) => {
+                    Some(Message::CancelRename)
+                }
+                _ => None,
+            }));
+        }
+        if self.window_list.is_some() {
+            subscriptions.push(event::listen_with(|event, _, _| match event {
+                cosmic::iced::Event::Keyboard(keyboard::Event::KeyPressed {
+                    key: Key::Named(Named::Escape),
+                    ..
+                }) => Some(Message::CloseWindowList),
+                _ => None,
+            }));
+        }
+        if self.config.heatmap {
+            // the focused workspace heats up while it stays focused
+            subscriptions.push(time::every(Duration::from_secs(1)).map(Message::Tick));
+        }
+        if self.error.is_some() {
+            subscriptions.push(time::every(Duration::from_secs(1)).map(Message::ExpireError));
+        }
+        if self.is_animating() {
+            subscriptions.push(time::every(FRAME_INTERVAL).map(Message::Tick));
+        }
+        Subscription::batch(subscriptions)
     }
 
     fn style(&self) -> Option<cosmic::iced_runtime::Appearance> {
         Some(cosmic::applet::style())
     }
 }
+
+/// Workspaces to show, in display order, only those on `output` if it's given and grouped by
+/// output otherwise. Workspaces niri doesn't place on any output are always shown.
+///
+/// Kept apart from `view()` so the ordering can be tested without a running applet.
+fn displayed<'a>(
+    state: &'a WorkspaceState,
+    config: &Config,
+    order: &OrderMemory,
+    output: Option<&str>,
+) -> Vec<&'a Workspace> {
+    let mut displayed: Vec<&Workspace> = state
+        .workspaces()
+        .iter()
+        .filter(|w| output.is_none() || w.output.is_none() || w.output.as_deref() == output)
+        .filter(|w| {
+            let trailing = config.hide_trailing_empty && state.is_trailing_empty(w);
+            let empty = config.hide_empty && state.is_empty(w.id);
+            w.is_active || !(trailing || empty)
+        })
+        .filter(|w| {
+            let Some(pattern) = &config.name_pattern else {
+                return true;
+            };
+            match &w.name {
+                Some(name) => glob_match(pattern, name),
+                None => config.include_unnamed,
+            }
+        })
+        .collect();
+    order.sort(&mut displayed);
+    order::sort_by_names(&mut displayed, &config.workspace_order);
+    // stable, keeping the order within each output
+    displayed.sort_by(|a, b| a.output.cmp(&b.output));
+    displayed
+}
+
+/// Whether all of `text` matches `pattern`, where `*` matches any run of characters and `?`
+/// a single one.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // position of the last `*` and the text it was tried against, to backtrack to
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Message sent when the button of `workspace` is clicked, which is known to have no windows if
+/// `empty`.
+fn on_press(config: &Config, modifiers: Modifiers, workspace: &Workspace, empty: bool) -> Message {
+    if config.alt_click_closes_window && modifiers.alt() {
+        Message::CloseWindowOnWorkspace(workspace.id)
+    } else if config.double_click_action != DoubleClickAction::None
+        || (config.spawn_on_empty.is_some() && empty)
+    {
+        Message::WorkspaceClicked(workspace.id)
+    } else {
+        Message::ActivateWorkspace(workspace.id)
+    }
+}
+
+/// `pattern` with `{number}` replaced by the lowest number from 1 giving a name that isn't
+/// `taken`. Without the placeholder, a taken name gets `-2`, `-3` and so on appended.
+fn numbered_name(pattern: &str, taken: &[&str]) -> String {
+    let free = |name: &String| !taken.contains(&name.as_str());
+    if pattern.contains("{number}") {
+        return (1..)
+            .map(|n: u32| pattern.replace("{number}", &n.to_string()))
+            .find(free)
+            .unwrap_or_default();
+    }
+    let name = pattern.to_owned();
+    if free(&name) {
+        return name;
+    }
+    (2..)
+        .map(|n: u32| format!("{pattern}-{n}"))
+        .find(free)
+        .unwrap_or_default()
+}
+
+/// `buttons` joined into one rounded control with dividers between them.
+fn segmented<'a>(buttons: Vec<Element<'a, Message>>, metrics: &Metrics) -> Element<'a, Message> {
+    let (width, height) = match metrics.horizontal {
+        true => (1.0, metrics.thickness),
+        false => (metrics.thickness, 1.0),
+    };
+    let opacity = metrics.opacity.unwrap_or(1.0);
+    let mut segments = Vec::with_capacity(buttons.len() * 2);
+    for (i, button) in buttons.into_iter().enumerate() {
+        if i > 0 {
+            let divider = widget::container(widget::Space::new(width, height));
+            segments.push(divider.class(style::divider(opacity)).into());
+        }
+        segments.push(button);
+    }
+    let control = match metrics.horizontal {
+        true => Element::from(widget::Row::with_children(segments)),
+        false => widget::Column::with_children(segments).into(),
+    };
+    widget::container(control)
+        .class(style::pill(metrics.shape, opacity))
+        .clip(true)
+        .into()
+}
+
+/// Workspace `steps` along `displayed` from the focused one, or the active one on this output,
+/// wrapping around at the ends unless it stops there. None when that's where it already is.
+fn scroll_target(displayed: &[&Workspace], steps: i32, stop_at_ends: bool) -> Option<u64> {
+    let current = displayed
+        .iter()
+        .position(|w| w.is_focused)
+        .or_else(|| displayed.iter().position(|w| w.is_active))?;
+    let len = displayed.len() as i64;
+    let target = current as i64 + i64::from(steps);
+    let target = match stop_at_ends {
+        true => target.clamp(0, len - 1),
+        false => target.rem_euclid(len),
+    } as usize;
+    (target != current).then(|| displayed[target].id)
+}
+
+/// Row of buttons along a horizontal panel, or a column along a vertical one.
+fn line<'a>(
+    children: Vec<Element<'a, Message>>,
+    horizontal: bool,
+    spacing: f32,
+) -> Element<'a, Message> {
+    match horizontal {
+        true => widget::Row::with_children(children).spacing(spacing).into(),
+        false => widget::Column::with_children(children)
+            .spacing(spacing)
+            .into(),
+    }
+}
+
+/// Small icon button with `text` in its tooltip and as its accessible name.
+fn badge<'a>(
+    icon: &'static str,
+    text: String,
+    on_press: Message,
+    position: tooltip::Position,
+) -> Element<'a, Message> {
+    let icon = widget::icon::from_name(icon).size(16).icon();
+    let button = widget::button::custom(icon)
+        .class(cosmic::theme::Button::Text)
+        .name(text.clone())
+        .on_press(on_press);
+    widget::tooltip(button, widget::text::body(text), position).into()
+}
+
+/// Moves `current` by at most `step` towards `target`.
+fn approach(current: f32, target: f32, step: f32) -> f32 {
+    match current < target {
+        true => (current + step).min(target),
+        false => (current - step).max(target),
+    }
+}
+
+/// Eased progress of an animation `t` of the way through, both in `0.0..=1.0`.
+///
+/// Animations step their linear progress towards `0.0` or `1.0` and ease it when drawn, so
+/// every curve runs backwards the same way when an animation reverses.
+fn apply_easing(t: f32, curve: EasingCurve) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    match curve.bounded() {
+        EasingCurve::Linear => t,
+        EasingCurve::EaseIn => t * t * t,
+        EasingCurve::EaseOut => 1.0 - (1.0 - t).powi(3),
+        EasingCurve::EaseInOut => match t < 0.5 {
+            true => 4.0 * t * t * t,
+            false => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+        },
+        EasingCurve::Spring(damping, stiffness) => spring(t, damping, stiffness),
+    }
+}
+
+/// Position of a unit mass on a spring pulling it from `0.0` to `1.0`, simulated over the
+/// animation's duration as one unit of time.
+///
+/// The spring may not have settled by the end, so the distance it has left then is made up
+/// for gradually over the whole animation, landing on `1.0` exactly at the end without a jump.
+fn spring(t: f32, damping: f32, stiffness: f32) -> f32 {
+    const STEPS: u32 = 120;
+    if t >= 1.0 {
+        return 1.0;
+    }
+    let simulate = |t: f32| {
+        let dt = 1.0 / STEPS as f32;
+        let (mut position, mut velocity) = (0.0f32, 0.0f32);
+        for _ in 0..(t * STEPS as f32) as u32 {
+            velocity += (stiffness * (1.0 - position) - damping * velocity) * dt;
+            position += velocity * dt;
+        }
+        position
+    };
+    simulate(t) + (1.0 - simulate(1.0)) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspaces::config::WorkspaceTemplate;
+
+    fn workspace(id: u64, idx: u8, name: Option<&str>, active_window_id: Option<u64>) -> Workspace {
+        Workspace {
+            id,
+            idx,
+            name: name.map(str::to_owned),
+            output: Some("DP-1".to_owned()),
+            is_active: id == 1,
+            is_focused: id == 1,
+            active_window_id,
+        }
+    }
+
+    /// Ids the displayed buttons activate or act on, in display order.
+    fn targets(state: &WorkspaceState, config: &Config, modifiers: Modifiers) -> Vec<u64> {
+        displayed(state, config, &OrderMemory::default(), None)
+            .into_iter()
+            .map(|w| {
+                let empty = state.is_empty(w.id);
+                match on_press(config, modifiers, w, empty) {
+                    Message::ActivateWorkspace(id)
+                    | Message::CloseWindowOnWorkspace(id)
+                    | Message::WorkspaceClicked(id) => {
+                        assert_eq!(id, w.id);
+                        id
+                    }
+                    message => panic!("unexpected {message:?}"),
+                }
+            })
+            .collect()
+    }
+
+    fn window(id: u64, workspace_id: u64, app_id: &str, title: &str) -> Window {
+        Window {
+            id,
+            title: Some(title.to_owned()),
+            app_id: Some(app_id.to_owned()),
+            pid: None,
+            workspace_id: Some(workspace_id),
+            is_focused: false,
+            is_floating: false,
+        }
+    }
+
+    fn app(config: Config, workspaces: Vec<Workspace>, windows: Vec<Window>) -> WorkspacesApp {
+        let mut app = WorkspacesApp::new(Core::default(), config);
+        // nothing remembered on the machine running the tests
+        app.order = OrderMemory::default();
+        app.state.set_workspaces(workspaces);
+        app.state.set_windows(windows);
+        app.lay_out();
+        app
+    }
+
+    /// The indicators of `app` one per line, each followed by its tooltip indented.
+    fn snapshot(app: &WorkspacesApp) -> Vec<String> {
+        let mut lines = Vec::new();
+        for indicator in app.indicators() {
+            let mut line = format!("#{} {:?}", indicator.id, indicator.label);
+            if indicator.active {
+                line.push_str(" active");
+            }
+            if indicator.hollow {
+                line.push_str(" hollow");
+            }
+            for icon in &indicator.icons {
+                match icon {
+                    AppIcon::Themed(_) => line.push_str(" [icon]"),
+                    AppIcon::Letter { letter, .. } => line.push_str(&format!(" [{letter}]")),
+                }
+            }
+            if indicator.overflow > 0 {
+                line.push_str(&format!(" +{}", indicator.overflow));
+            }
+            if let Some(badge) = &indicator.badge {
+                line.push_str(&format!(" <{badge}>"));
+            }
+            if let Some(count) = indicator.window_count {
+                line.push_str(&format!(" ({count})"));
+            }
+            line.push_str(&format!(" -> {:?}", indicator.on_press));
+            lines.push(line);
+            // fluent wraps arguments in direction isolates
+            let tooltip = indicator.tooltip.unwrap_or_default();
+            let tooltip = tooltip.replace(['\u{2068}', '\u{2069}'], "");
+            lines.extend(tooltip.lines().map(|line| format!("    {line}")));
+        }
+        lines
+    }
+
+    #[test]
+    fn snapshot_single_output() {
+        let config = Config {
+            show_window_icons: true,
+            show_window_counts: true,
+            ..Default::default()
+        };
+        let app = app(
+            config,
+            vec![
+                workspace(1, 1, None, Some(10)),
+                workspace(2, 2, Some("web"), Some(20)),
+                workspace(3, 3, None, None),
+            ],
+            vec![
+                window(10, 1, "kitty", "shell"),
+                window(11, 1, "kitty", "logs"),
+                window(20, 2, "firefox", "news"),
+            ],
+        );
+        assert_eq!(
+            snapshot(&app),
+            [
+                r#"#1 "1" active [K] -> ActivateWorkspace(1)"#,
+                "    2 windows",
+                "    last: kitty — shell",
+                r#"#2 "web" [F] -> ActivateWorkspace(2)"#,
+                "    1 window",
+                "    last: firefox — news",
+                r#"#3 "3" -> ActivateWorkspace(3)"#,
+                "    no windows",
+            ]
+        );
+    }
+
+    #[test]
+    fn snapshot_dual_output() {
+        let config = Config {
+            hide_trailing_empty: true,
+            ..Default::default()
+        };
+        let chat = Workspace {
+            output: Some("HDMI-1".to_owned()),
+            is_active: true,
+            ..workspace(3, 1, Some("chat"), None)
+        };
+        let app = app(
+            config,
+            vec![
+                workspace(1, 1, None, Some(10)),
+                workspace(2, 2, None, None),
+                chat,
+            ],
+            vec![window(10, 1, "kitty", "shell")],
+        );
+        // DP-1's trailing empty workspace is hidden, HDMI-1's is kept since it's active
+        assert_eq!(
+            snapshot(&app),
+            [
+                r#"#1 "1" active -> ActivateWorkspace(1)"#,
+                r#"#3 "chat" active -> ActivateWorkspace(3)"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn locked_applet_doesnt_create_workspaces() {
+        let config = Config {
+            workspace_templates: vec![WorkspaceTemplate {
+                name: "Web".to_owned(),
+                create_name: "web-{number}".to_owned(),
+                initial_apps: vec!["firefox".to_owned()],
+            }],
+            ..Default::default()
+        };
+        let workspaces = vec![workspace(1, 1, None, Some(10)), workspace(2, 2, None, None)];
+        let windows = vec![window(10, 1, "kitty", "shell")];
+        let mut app = app(config, workspaces, windows);
+        let (sender, receiver) = mpsc::channel();
+        app.sender = Some(sender);
+
+        app.focus_locked = true;
+        let _ = app.create_workspace(Some(0));
+        assert!(receiver.try_recv().is_err());
+
+        app.focus_locked = false;
+        let _ = app.create_workspace(Some(0));
+        let sent: Vec<Action> = receiver.try_iter().collect();
+        assert!(matches!(
+            sent.as_slice(),
+            [
+                Action::FocusWorkspace { .. },
+                Action::SetWorkspaceName { .. },
+                Action::Spawn { .. },
+            ]
+        ));
+    }
+
+    #[test]
+    fn spawns_only_on_workspaces_known_to_be_empty() {
+        let config = Config {
+            spawn_on_empty: Some("foot".to_owned()),
+            ..Default::default()
+        };
+        let workspaces = vec![workspace(1, 1, None, None), workspace(2, 2, None, None)];
+        let mut app = app(config, workspaces, Vec::new());
+        let (sender, receiver) = mpsc::channel();
+        app.sender = Some(sender);
+        let double_click = |app: &mut WorkspacesApp| {
+            let _ = app.update(Message::WorkspaceClicked(2));
+            let _ = app.update(Message::WorkspaceClicked(2));
+        };
+
+        // niri's windows aren't known yet, the workspace may well have some
+        app.state.forget_windows();
+        let workspace = app.state.workspace(2).cloned().unwrap();
+        assert!(matches!(
+            app.on_press(&workspace),
+            Message::ActivateWorkspace(2)
+        ));
+        double_click(&mut app);
+        assert!(receiver.try_recv().is_err());
+
+        app.state.set_windows(Vec::new());
+        assert!(matches!(
+            app.on_press(&workspace),
+            Message::WorkspaceClicked(2)
+        ));
+        double_click(&mut app);
+        let sent: Vec<Action> = receiver.try_iter().collect();
+        assert!(matches!(
+            sent.as_slice(),
+            [Action::FocusWorkspace { .. }, Action::Spawn { .. }]
+        ));
+    }
+
+    #[test]
+    fn ripples_spread_from_the_pressed_point() {
+        let workspaces = vec![workspace(1, 1, None, None), workspace(2, 2, None, None)];
+        let mut app = app(Config::default(), workspaces, Vec::new());
+        let (sender, _receiver) = mpsc::channel();
+        app.sender = Some(sender);
+
+        // switching without pressing the button, e.g. by scrolling over another one
+        let _ = app.update(Message::PointerMoved(2, Point::new(3.0, 4.0)));
+        let _ = app.update(Message::ActivateWorkspace(1));
+        assert!(app.ripples.is_empty());
+
+        let _ = app.update(Message::PointerMoved(2, Point::new(5.0, 6.0)));
+        let _ = app.update(Message::ActivateWorkspace(2));
+        let ripple = app.ripples[&2];
+        assert_eq!(ripple.center, Point::new(5.0, 6.0));
+        assert_eq!((ripple.radius, ripple.alpha), (0.0, 1.0));
+
+        let mut now = app.last_frame;
+        now += FRAME_INTERVAL;
+        let _ = app.update(Message::Tick(now));
+        let ripple = app.ripples[&2];
+        assert!(ripple.radius > 0.0 && ripple.alpha < 1.0);
+        for _ in 0..10 {
+            now += FRAME_INTERVAL;
+            let _ = app.update(Message::Tick(now));
+        }
+        assert!(app.ripples.is_empty());
+    }
+
+    #[test]
+    fn reduced_motion_stops_animating() {
+        let config = Config {
+            neon_mode: true,
+            reduce_motion: true,
+            ..Config::default()
+        };
+        let workspaces = vec![workspace(1, 1, None, None), workspace(2, 2, None, None)];
+        let mut app = app(config, workspaces, Vec::new());
+        let (sender, _receiver) = mpsc::channel();
+        app.sender = Some(sender);
+
+        let _ = app.update(Message::PointerMoved(2, Point::new(5.0, 6.0)));
+        let _ = app.update(Message::ActivateWorkspace(2));
+        assert!(app.ripples.is_empty());
+        // a new workspace shows at full size right away
+        let mut workspaces = app.state.workspaces().to_vec();
+        workspaces.push(workspace(3, 3, None, None));
+        let _ = app.update(Message::WorkspaceUpdate(workspaces));
+        assert!(!app.is_animating());
+    }
+
+    #[test]
+    fn keeps_the_memory_last_read() {
+        let workspaces = vec![workspace(1, 1, None, None)];
+        let mut app = app(Config::default(), workspaces, Vec::new());
+        let read = |bytes| Message::MemoryRead {
+            workspace_id: 1,
+            bytes,
+        };
+        let _ = app.update(read(Some(1024)));
+        assert_eq!(app.memory.get(&1), Some(&1024));
+        // its processes ended since
+        let _ = app.update(read(None));
+        assert!(app.memory.is_empty());
+    }
+
+    #[test]
+    fn muted_applet_doesnt_switch() {
+        let workspaces = vec![workspace(1, 1, None, None), workspace(2, 2, None, None)];
+        let mut app = app(Config::default(), workspaces, Vec::new());
+        let (sender, receiver) = mpsc::channel();
+        app.sender = Some(sender);
+
+        app.muted = true;
+        let _ = app.update(Message::ActivateWorkspace(2));
+        assert!(receiver.try_recv().is_err());
+        assert!(app.pending.is_empty());
+        assert!(app.ripples.is_empty());
+
+        app.muted = false;
+        let _ = app.update(Message::ActivateWorkspace(2));
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(Action::FocusWorkspace { .. })
+        ));
+    }
+
+    #[test]
+    fn scrolling_wraps_around_or_stops() {
+        let workspaces = [
+            workspace(1, 1, None, None),
+            workspace(2, 2, None, None),
+            workspace(3, 3, None, None),
+        ];
+        let displayed: Vec<&Workspace> = workspaces.iter().collect();
+        assert_eq!(scroll_target(&displayed, 1, false), Some(2));
+        assert_eq!(scroll_target(&displayed, -1, false), Some(3));
+        assert_eq!(scroll_target(&displayed, 4, false), Some(2));
+        assert_eq!(scroll_target(&displayed, 3, false), None);
+        // nothing to start from
+        assert_eq!(scroll_target(&displayed[1..], 1, false), None);
+
+        assert_eq!(scroll_target(&displayed, -1, true), None);
+        assert_eq!(scroll_target(&displayed, 5, true), Some(3));
+    }
+
+    #[test]
+    fn snapshot_empty() {
+        let config = Config {
+            outline_empty: true,
+            show_window_badges: true,
+            ..Default::default()
+        };
+        let workspaces = vec![
+            workspace(1, 1, None, None),
+            workspace(2, 2, None, Some(20)),
+            workspace(3, 3, None, None),
+            workspace(4, 4, None, None),
+        ];
+        let windows = vec![window(20, 2, "kitty", "shell")];
+        let mut app = app(config, workspaces, windows);
+        assert_eq!(
+            snapshot(&app),
+            [
+                r#"#1 "1" active hollow -> ActivateWorkspace(1)"#,
+                r#"#2 "2" (1) -> ActivateWorkspace(2)"#,
+                r#"#3 "3" hollow -> ActivateWorkspace(3)"#,
+                r#"#4 "4" hollow -> ActivateWorkspace(4)"#,
+            ]
+        );
+
+        // the active workspace stays even when it's empty
+        app.config.hide_empty = true;
+        app.lay_out();
+        assert_eq!(
+            snapshot(&app),
+            [
+                r#"#1 "1" active hollow -> ActivateWorkspace(1)"#,
+                r#"#2 "2" (1) -> ActivateWorkspace(2)"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn snapshot_numbers() {
+        let config = Config {
+            indicator_style: IndicatorStyle::Number,
+            labels: HashMap::from([("web".to_owned(), "W".to_owned())]),
+            ..Default::default()
+        };
+        let workspaces = vec![
+            workspace(1, 1, Some("web"), None),
+            workspace(2, 2, Some("chat"), None),
+        ];
+        let app = app(config, workspaces, Vec::new());
+        assert_eq!(
+            snapshot(&app),
+            [
+                r#"#1 "1" active -> ActivateWorkspace(1)"#,
+                r#"#2 "2" -> ActivateWorkspace(2)"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn snapshot_panel_output() {
+        let chat = Workspace {
+            output: Some("HDMI-1".to_owned()),
+            is_active: true,
+            ..workspace(3, 1, Some("chat"), None)
+        };
+        let nowhere = Workspace {
+            output: None,
+            ..workspace(4, 1, Some("scratch"), None)
+        };
+        let mut app = app(
+            Config::default(),
+            vec![workspace(1, 1, None, None), chat, nowhere],
+            Vec::new(),
+        );
+        app.core.applet.output_name = "HDMI-1".to_owned();
+        app.lay_out();
+        assert_eq!(
+            snapshot(&app),
+            [
+                r#"#4 "scratch" -> ActivateWorkspace(4)"#,
+                r#"#3 "chat" active -> ActivateWorkspace(3)"#,
+            ]
+        );
+
+        // an output niri doesn't know shows all of them
+        app.core.applet.output_name = "eDP-1".to_owned();
+        app.lay_out();
+        assert_eq!(snapshot(&app).len(), 3);
+    }
+
+    #[test]
+    fn snapshot_filtered() {
+        let config = Config {
+            name_pattern: Some("project-*".to_owned()),
+            workspace_order: vec!["project-b".to_owned()],
+            labels: HashMap::from([("project-b".to_owned(), "B".to_owned())]),
+            ..Default::default()
+        };
+        let app = app(
+            config,
+            vec![
+                workspace(1, 1, Some("project-a"), None),
+                workspace(2, 2, Some("mail"), None),
+                workspace(3, 3, Some("project-b"), None),
+                workspace(4, 4, None, None),
+            ],
+            Vec::new(),
+        );
+        assert_eq!(
+            snapshot(&app),
+            [
+                r#"#3 "B" -> ActivateWorkspace(3)"#,
+                r#"#1 "project-a" active -> ActivateWorkspace(1)"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn snapshot_overflow_and_pending() {
+        let config = Config {
+            show_window_icons: true,
+            stack_window_icons: true,
+            show_pending: true,
+            show_layout_indicator: true,
+            ..Default::default()
+        };
+        let mut app = app(
+            config,
+            vec![workspace(1, 1, None, Some(13)), workspace(2, 2, None, None)],
+            vec![
+                window(10, 1, "alacritty", "shell"),
+                window(11, 1, "blender", "render"),
+                window(12, 1, "code", "editor"),
+                window(13, 1, "dino", "chat"),
+                window(14, 1, "evince", "paper"),
+            ],
+        );
+        app.pending.insert(2, Instant::now());
+        // the last focused window comes first
+        assert_eq!(
+            snapshot(&app),
+            [
+                r#"#1 "1" active [D] [A] [B] +2 <view-dual-symbolic> -> ActivateWorkspace(1)"#,
+                "    dino — chat",
+                "    alacritty — shell",
+                "    blender — render",
+                "    code — editor",
+                "    evince — paper",
+                r#"#2 "2" <content-loading-symbolic> -> ActivateWorkspace(2)"#,
+                "    Switching…",
+            ]
+        );
+    }
+
+    #[test]
+    fn buttons_target_their_workspace() {
+        let mut state = WorkspaceState::default();
+        // ids deliberately out of line with idx, the last one is niri's trailing empty workspace
+        state.set_workspaces(vec![
+            workspace(7, 2, Some("web"), Some(70)),
+            workspace(1, 1, None, Some(10)),
+            workspace(4, 3, Some("mail"), Some(40)),
+            workspace(9, 4, None, None),
+        ]);
+        let mut config = Config::default();
+        assert_eq!(targets(&state, &config, Modifiers::empty()), [1, 4, 7, 9]);
+
+        config.hide_trailing_empty = true;
+        config.workspace_order = vec!["mail".to_owned(), "web".to_owned()];
+        assert_eq!(targets(&state, &config, Modifiers::empty()), [4, 7, 1]);
+
+        config.alt_click_closes_window = true;
+        let closes_windows = displayed(&state, &config, &OrderMemory::default(), None)
+            .into_iter()
+            .all(|w| {
+                matches!(
+                    on_press(&config, Modifiers::ALT, w, state.is_empty(w.id)),
+                    Message::CloseWindowOnWorkspace(id) if id == w.id
+                )
+            });
+        assert!(closes_windows);
+        assert_eq!(targets(&state, &config, Modifiers::ALT), [4, 7, 1]);
+
+        config.double_click_action = DoubleClickAction::MoveWindowHere;
+        assert_eq!(targets(&state, &config, Modifiers::empty()), [4, 7, 1]);
+    }
+
+    #[test]
+    fn filters_by_name_pattern() {
+        let mut state = WorkspaceState::default();
+        state.set_workspaces(vec![
+            workspace(1, 1, Some("project-a"), None),
+            workspace(2, 2, Some("mail"), None),
+            workspace(3, 3, Some("project-b"), None),
+            workspace(4, 4, None, None),
+        ]);
+        let mut config = Config {
+            name_pattern: Some("project-*".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(targets(&state, &config, Modifiers::empty()), [1, 3]);
+
+        config.include_unnamed = true;
+        assert_eq!(targets(&state, &config, Modifiers::empty()), [1, 3, 4]);
+    }
+
+    #[test]
+    fn easing_keeps_the_ends() {
+        let curves = [
+            EasingCurve::Linear,
+            EasingCurve::EaseIn,
+            EasingCurve::EaseOut,
+            EasingCurve::EaseInOut,
+            EasingCurve::Spring(10.0, 200.0),
+        ];
+        for curve in curves {
+            assert_eq!(apply_easing(0.0, curve), 0.0, "{curve:?}");
+            assert_eq!(apply_easing(1.0, curve), 1.0, "{curve:?}");
+        }
+        assert!(apply_easing(0.5, EasingCurve::EaseIn) < 0.5);
+        assert!(apply_easing(0.5, EasingCurve::EaseOut) > 0.5);
+        assert_eq!(apply_easing(0.5, EasingCurve::EaseInOut), 0.5);
+        // barely damped, so it shoots past its rest position
+        let overshoot =
+            (1..20).any(|i| apply_easing(i as f32 / 20.0, EasingCurve::Spring(2.0, 200.0)) > 1.0);
+        assert!(overshoot);
+    }
+
+    #[test]
+    fn springs_stay_finite_and_settle() {
+        let curves = [
+            EasingCurve::Spring(2.0, 200.0),
+            EasingCurve::Spring(0.0, 1e9),
+            EasingCurve::Spring(-50.0, -50.0),
+            EasingCurve::Spring(f32::NAN, f32::INFINITY),
+        ];
+        for curve in curves {
+            let values: Vec<f32> = (0..=100)
+                .map(|i| apply_easing(i as f32 / 100.0, curve))
+                .collect();
+            assert!(values.iter().all(|v| v.is_finite()), "{curve:?}");
+            // the last frame moves no further than the ones before, instead of jumping
+            let step = |pair: &[f32]| (pair[1] - pair[0]).abs();
+            let steps: Vec<f32> = values.windows(2).map(step).collect();
+            let largest = steps[..99].iter().copied().fold(0.0, f32::max);
+            assert!(steps[99] <= largest + 1e-3, "{curve:?}: {}", steps[99]);
+        }
+    }
+
+    #[test]
+    fn numbers_template_names() {
+        assert_eq!(numbered_name("dev-{number}", &[]), "dev-1");
+        assert_eq!(numbered_name("dev-{number}", &["dev-1", "dev-3"]), "dev-2");
+        assert_eq!(numbered_name("mail", &["web"]), "mail");
+        assert_eq!(numbered_name("mail", &["mail", "mail-2"]), "mail-3");
+    }
+
+    #[test]
+    fn matches_globs() {
+        assert!(glob_match("project-*", "project-cosmic"));
+        assert!(glob_match("project-*", "project-"));
+        assert!(glob_match("*-?", "web-2"));
+        assert!(glob_match("a*b*c", "axxbyybc"));
+        assert!(!glob_match("project-*", "my-project-a"));
+        assert!(!glob_match("*-?", "web-10"));
+        assert!(!glob_match("mail", "mail2"));
+    }
+}
@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use niri_ipc::Workspace;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Keyboard shortcuts that niri's config binds to focusing a workspace.
+///
+/// Only single-line binds like `Mod+1 { focus-workspace 1; }` are understood, this is a hint
+/// for tooltips rather than a KDL parser.
+#[derive(Debug, Default)]
+pub struct Shortcuts {
+    by_index: HashMap<u8, String>,
+    by_name: HashMap<String, String>,
+}
+
+impl Shortcuts {
+    /// Reads the binds from niri's config at `path`, or none if it can't be read.
+    pub fn load(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(kdl) => Self::parse(&kdl),
+            Err(e) => {
+                eprintln!("failed to read {}: {e}", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    fn parse(kdl: &str) -> Self {
+        let mut shortcuts = Self::default();
+        for line in kdl.lines() {
+            let line = line.split("//").next().unwrap_or_default().trim();
+            let Some((head, body)) = line.split_once('{') else {
+                continue;
+            };
+            let Some(key) = head.split_whitespace().next() else {
+                continue;
+            };
+            let action = body.split([';', '}']).next().unwrap_or_default().trim();
+            let Some(target) = action.strip_prefix("focus-workspace ") else {
+                continue;
+            };
+            let target = target.trim();
+            if let Some(name) = target.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+                shortcuts
+                    .by_name
+                    .entry(name.to_owned())
+                    .or_insert_with(|| key.to_owned());
+            } else if let Ok(idx) = target.parse() {
+                shortcuts
+                    .by_index
+                    .entry(idx)
+                    .or_insert_with(|| key.to_owned());
+            }
+        }
+        shortcuts
+    }
+
+    /// Shortcut focusing `workspace`, preferring binds to its name over binds to its index.
+    pub fn get(&self, workspace: &Workspace) -> Option<&str> {
+        let by_name = workspace
+            .name
+            .as_ref()
+            .and_then(|name| self.by_name.get(name));
+        by_name
+            .or_else(|| self.by_index.get(&workspace.idx))
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(idx: u8, name: Option<&str>) -> Workspace {
+        Workspace {
+            id: u64::from(idx),
+            idx,
+            name: name.map(str::to_owned),
+            output: None,
+            is_active: false,
+            is_focused: false,
+            active_window_id: None,
+        }
+    }
+
+    #[test]
+    fn parses_workspace_binds() {
+        let shortcuts = Shortcuts::parse(
+            r#"
+            binds {
+                Mod+1 { focus-workspace 1; }
+                Mod+Ctrl+1 { move-column-to-workspace 1; }
+                Mod+Page_Down { focus-workspace-down; }
+                Mod+2 hotkey-overlay-title="Second" { focus-workspace 2; }
+                Mod+Shift+2 { focus-workspace 2; }
+                // Mod+3 { focus-workspace 3; }
+                Mod+W { focus-workspace "web"; }
+            }
+            "#,
+        );
+        assert_eq!(shortcuts.get(&workspace(1, None)), Some("Mod+1"));
+        assert_eq!(shortcuts.get(&workspace(2, None)), Some("Mod+2"));
+        assert_eq!(shortcuts.get(&workspace(3, None)), None);
+        assert_eq!(shortcuts.get(&workspace(1, Some("web"))), Some("Mod+W"));
+        assert_eq!(shortcuts.get(&workspace(2, Some("mail"))), Some("Mod+2"));
+    }
+}
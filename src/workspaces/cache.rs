@@ -0,0 +1,151 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use niri_ipc::Workspace;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::{env, fs, io};
+
+/// Workspaces last reported by niri, so the applet has something to show right away when the
+/// panel restarts, before it's connected again.
+///
+/// Kept per output in `$XDG_CACHE_HOME/cosmic-applet-niri`, as the panel on each output may
+/// filter them differently.
+#[derive(Debug, Default)]
+pub struct SnapshotCache {
+    path: Option<PathBuf>,
+}
+
+/// The parts of a workspace the applet draws before niri tells it more.
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedWorkspace {
+    id: u64,
+    idx: u8,
+    name: Option<String>,
+    output: Option<String>,
+    is_active: bool,
+    is_focused: bool,
+}
+
+impl SnapshotCache {
+    /// Cache of the panel on `output`.
+    pub fn new(output: &str) -> Self {
+        Self {
+            path: cache_dir().map(|dir| dir.join(format!("workspaces-{output}.json"))),
+        }
+    }
+
+    /// The cached workspaces, none if there aren't any or they can't be read.
+    pub fn load(&self) -> Vec<Workspace> {
+        let Some(path) = &self.path else {
+            return Vec::new();
+        };
+        let json = match fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Vec::new(),
+            Err(e) => {
+                eprintln!("failed to read {}: {e}", path.display());
+                return Vec::new();
+            }
+        };
+        match serde_json::from_str::<Vec<CachedWorkspace>>(&json) {
+            Ok(cached) => cached.into_iter().map(Workspace::from).collect(),
+            Err(e) => {
+                eprintln!("ignoring invalid {}: {e}", path.display());
+                Vec::new()
+            }
+        }
+    }
+
+    pub fn save(&self, workspaces: &[Workspace]) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let cached: Vec<CachedWorkspace> = workspaces.iter().map(CachedWorkspace::from).collect();
+        fs::write(path, serde_json::to_string(&cached)?)
+    }
+}
+
+impl From<&Workspace> for CachedWorkspace {
+    fn from(workspace: &Workspace) -> Self {
+        Self {
+            id: workspace.id,
+            idx: workspace.idx,
+            name: workspace.name.clone(),
+            output: workspace.output.clone(),
+            is_active: workspace.is_active,
+            is_focused: workspace.is_focused,
+        }
+    }
+}
+
+impl From<CachedWorkspace> for Workspace {
+    fn from(cached: CachedWorkspace) -> Self {
+        Self {
+            id: cached.id,
+            idx: cached.idx,
+            name: cached.name,
+            output: cached.output,
+            is_active: cached.is_active,
+            is_focused: cached.is_focused,
+            active_window_id: None,
+        }
+    }
+}
+
+/// `$XDG_CACHE_HOME/cosmic-applet-niri`, defaulting to `~/.cache/cosmic-applet-niri`.
+fn cache_dir() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".cache"),
+    };
+    Some(base.join("cosmic-applet-niri"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restores_saved_workspaces() {
+        let path = env::temp_dir().join(format!("cosmic-applet-niri-{}.json", std::process::id()));
+        let cache = SnapshotCache {
+            path: Some(path.clone()),
+        };
+        assert!(cache.load().is_empty());
+
+        let workspaces = vec![
+            Workspace {
+                id: 3,
+                idx: 1,
+                name: Some("web".to_owned()),
+                output: Some("DP-1".to_owned()),
+                is_active: true,
+                is_focused: true,
+                active_window_id: Some(30),
+            },
+            Workspace {
+                id: 4,
+                idx: 2,
+                name: None,
+                output: Some("DP-1".to_owned()),
+                is_active: false,
+                is_focused: false,
+                active_window_id: None,
+            },
+        ];
+        cache.save(&workspaces).unwrap();
+        let loaded = cache.load();
+        fs::remove_file(&path).unwrap();
+
+        let summary: Vec<(u64, u8, Option<&str>, bool)> = loaded
+            .iter()
+            .map(|w| (w.id, w.idx, w.name.as_deref(), w.is_active))
+            .collect();
+        assert_eq!(summary, [(3, 1, Some("web"), true), (4, 2, None, false)]);
+        // windows aren't known until niri reports them
+        assert!(loaded.iter().all(|w| w.active_window_id.is_none()));
+    }
+}
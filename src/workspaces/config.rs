@@ -0,0 +1,616 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
+use niri_ipc::{Action, Workspace};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::render::IndicatorStyle;
+
+/// Persistent applet settings, stored through cosmic-config under the applet's `APP_ID`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, CosmicConfigEntry)]
+#[version = 1]
+#[serde(default)]
+pub struct Config {
+    /// How each workspace is drawn.
+    pub indicator_style: IndicatorStyle,
+    /// Join the workspace buttons into one segmented control instead of separate buttons.
+    pub segmented: bool,
+    /// Pulse a glow around the active workspace button.
+    pub neon_mode: bool,
+    /// Keep the buttons invisible until the pointer is over the applet.
+    pub stealth_mode: bool,
+    /// White dots on black buttons, to reduce OLED burn-in.
+    pub monochrome_mode: bool,
+    /// Switch to [`Config::monochrome_mode`] while power saving is on, following
+    /// power-profiles-daemon's power saver profile that COSMIC's power saving mode sets.
+    pub monochrome_when_power_saving: bool,
+    /// Outlined, see-through buttons for translucent panels, only the active one is filled.
+    pub flat_mode: bool,
+    /// Shimmer and badge a clicked button until niri confirms the switch.
+    pub show_pending: bool,
+    /// Only show the active workspace and its direct neighbours on every output.
+    pub zen_mode: bool,
+    /// Course of the fades and size changes over their duration.
+    pub animation_easing: EasingCurve,
+    /// Leave out the neon pulse, ripples and size changes, buttons get their new size at once.
+    ///
+    /// Set here as COSMIC has no reduce motion preference applets could follow yet.
+    pub reduce_motion: bool,
+    /// Workspace names on buttons twice the usual size, without badges or counts, readable in
+    /// screen recordings.
+    pub presentation_mode: bool,
+    /// Show a button toggling [`Config::presentation_mode`].
+    pub show_presentation_toggle: bool,
+    /// Show a button toggling the focused window between floating and tiled.
+    pub show_floating_toggle: bool,
+    /// Colour buttons by the time spent on their workspace this session, from cold to hot.
+    pub heatmap: bool,
+    pub heatmap_colors: HeatmapColors,
+    /// Alt+clicking a workspace button closes the window focused on that workspace.
+    ///
+    /// This is a power-user shortcut: the window is closed without confirmation.
+    pub alt_click_closes_window: bool,
+    /// Switch workspaces as soon as a button is pressed instead of when it's released, which
+    /// feels closer to niri's keybinds.
+    pub activate_on_press: bool,
+    /// What double-clicking a workspace button does.
+    ///
+    /// Single clicks wait for a possible second click unless this is
+    /// [`DoubleClickAction::None`], or on empty workspaces with [`Config::spawn_on_empty`].
+    pub double_click_action: DoubleClickAction,
+    /// Shell command that double-clicking an empty workspace focuses it and runs there, like a
+    /// terminal. Takes precedence over [`Config::double_click_action`] on empty workspaces.
+    pub spawn_on_empty: Option<String>,
+    /// Milliseconds in which activating the same workspace again is ignored, 150 if unset.
+    pub activation_debounce_ms: Option<u64>,
+    /// Events on inactive workspaces that show a desktop notification.
+    pub notify_on: Vec<WorkspaceEvent>,
+    /// Milliseconds after a notification in which no other one is shown, 5000 if unset.
+    pub notify_cooldown_ms: Option<u64>,
+    /// Smallest thickness of the buttons across the panel in pixels, 12 if unset.
+    pub min_button_thickness: Option<f32>,
+    /// Largest thickness of the buttons across the panel in pixels, 64 if unset.
+    pub max_button_thickness: Option<f32>,
+    /// Length of the buttons along the panel in pixels, 16 if unset.
+    pub button_length: Option<f32>,
+    /// Space between the buttons in pixels, 4 if unset.
+    pub button_spacing: Option<f32>,
+    /// Space around the applet in pixels, 8 if unset.
+    pub padding: Option<u16>,
+    /// Corners of the buttons, and of the segmented control.
+    pub button_shape: ButtonShape,
+    /// How the applet's length along the panel is chosen.
+    pub sizing: Sizing,
+    /// Background behind the whole applet, none if unset.
+    pub background: Option<AppletBackground>,
+    /// Wrap the buttons onto two lines when the panel is thick enough.
+    pub wrap_layout: bool,
+    /// Only wrap once more than this many workspaces are shown.
+    pub wrap_threshold: usize,
+    /// Hide the empty workspace niri keeps at the end of every output, unless it's active.
+    pub hide_trailing_empty: bool,
+    /// Hide every workspace without windows, unless it's active.
+    pub hide_empty: bool,
+    /// Draw the buttons of workspaces without windows as outlines.
+    pub outline_empty: bool,
+    /// Show the number of open windows across all workspaces.
+    pub show_window_total: bool,
+    /// End of the strip the window total is shown at.
+    pub window_total_position: Placement,
+    /// What scrolling over the window total does.
+    pub window_total_scroll: ScrollAction,
+    /// Don't switch workspaces by scrolling over the strip, e.g. to avoid switching by
+    /// accident on a touchpad.
+    pub disable_scroll_switching: bool,
+    /// Stop at the first and last workspace when scrolling over the strip instead of wrapping
+    /// around.
+    pub scroll_stops_at_ends: bool,
+    /// Show the number of windows of every workspace in its tooltip.
+    pub show_window_counts: bool,
+    /// Show the number of windows of every workspace that has any in a corner of its button.
+    pub show_window_badges: bool,
+    /// How floating windows are counted in window counts and the window total.
+    pub floating_windows: FloatingWindows,
+    /// Show the memory used by the apps of every workspace in its tooltip, read from `/proc`
+    /// in the background as the pointer gets onto the workspace's button.
+    pub show_resource_usage: bool,
+    /// Show the number of columns of every workspace in its tooltip.
+    pub show_column_counts: bool,
+    /// Mark workspaces with one or several tiled windows with a small icon.
+    pub show_layout_indicator: bool,
+    /// Icons of the layout indicator.
+    pub layout_icons: LayoutIcons,
+    /// Show the icon of the window last focused on each workspace, and name it in the tooltip.
+    pub show_window_icons: bool,
+    /// Stack the icons of up to three windows of each workspace, followed by the number of the
+    /// others, instead of only the last focused one. Tooltips then list all windows.
+    pub stack_window_icons: bool,
+    /// Mark workspaces that niri window rules route apps to.
+    pub show_app_targets: bool,
+    /// Workspace name to the app ids routed there, mirroring niri's `open-on-workspace` rules.
+    pub app_targets: HashMap<String, Vec<String>>,
+    /// Labels shown instead of the name or index of workspaces, see [`Config::label`].
+    pub labels: HashMap<String, String>,
+    /// Short names the focused output applet shows instead of output names, keyed by output
+    /// name.
+    pub output_aliases: HashMap<String, String>,
+    /// Part of the socket name of the niri instance to connect to, like `wayland-2` or its pid,
+    /// when several are running. Otherwise `$NIRI_SOCKET` is used.
+    pub niri_session: Option<String>,
+    /// niri's socket, used unless `$NIRI_SOCKET` is set, for setups where it's somewhere
+    /// unusual.
+    pub niri_socket: Option<PathBuf>,
+    /// Path of niri's config, read for the workspace shortcuts shown in tooltips.
+    pub niri_config: Option<PathBuf>,
+    /// Kinds of niri events the applet follows.
+    pub events: EventCategories,
+    /// Only show workspaces on the outputs with these names, or on all outputs if empty.
+    pub output_filter: Vec<String>,
+    /// Show the workspaces of every output, not only those of the output the panel is on.
+    ///
+    /// Without a filter, they're shown for every output anyway when niri doesn't know the
+    /// panel's output, see [`Config::output_filter`].
+    pub show_all_outputs: bool,
+    /// Only show workspaces whose name matches this glob pattern, like `project-*`.
+    ///
+    /// `*` matches any run of characters and `?` a single one.
+    pub name_pattern: Option<String>,
+    /// Keep showing unnamed workspaces while [`Config::name_pattern`] is set.
+    pub include_unnamed: bool,
+    /// Names of workspaces to show first, in this order, overriding the remembered order.
+    ///
+    /// Workspaces not listed here follow in their usual order.
+    pub workspace_order: Vec<String>,
+    /// Shell command run after switching workspaces through the applet, not for switches made
+    /// elsewhere. `{id}`, `{idx}`, `{name}` and `{output}` are replaced by the workspace's.
+    pub activation_command: Option<String>,
+    /// Show a lock button that stops clicks from switching workspaces while locked.
+    pub show_focus_lock: bool,
+    /// Show a button that stops the applet from sending any request to niri while pressed,
+    /// for recordings and presentations.
+    pub show_mute_toggle: bool,
+    /// Show back and forward buttons going through the workspaces focused before, like
+    /// browser history.
+    pub show_history_buttons: bool,
+    /// Extra buttons after the workspaces that run niri actions.
+    pub action_buttons: Vec<ActionButton>,
+    /// Workspaces the new workspace menu can set up. Its button is only shown when there are
+    /// any.
+    pub workspace_templates: Vec<WorkspaceTemplate>,
+    /// Settings overridden for the panel on a specific output, keyed by output name.
+    pub outputs: HashMap<String, OutputConfig>,
+}
+
+/// Length of the applet along the panel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Sizing {
+    /// Grow and shrink with the workspaces.
+    #[default]
+    Auto,
+    /// Grow and shrink with the workspaces up to this many pixels, cutting off the rest.
+    Clamped(f32),
+    /// Always this many pixels with the buttons centred, so workspace changes don't shift the
+    /// panel layout.
+    Fixed(f32),
+    /// Room for at least this many workspaces, showing fewer within it and only growing past
+    /// it.
+    Slots(usize),
+    /// Room for the most workspaces shown at once this session.
+    Stable,
+}
+
+/// How floating windows, like dialogs and calculators, enter window counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FloatingWindows {
+    /// Counted apart from the tiled windows, like "3 windows + 1 floating".
+    #[default]
+    Separate,
+    /// Not counted at all.
+    Exclude,
+}
+
+/// Colours of the least and most used workspace in the heatmap, as `#rrggbb`.
+///
+/// Invalid colours fall back to the defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeatmapColors {
+    pub cold: String,
+    pub hot: String,
+}
+
+impl Default for HeatmapColors {
+    fn default() -> Self {
+        Self {
+            cold: "#3b6ea5".to_owned(),
+            hot: "#e0533d".to_owned(),
+        }
+    }
+}
+
+/// Icon names of the layout indicator, see [`Config::show_layout_indicator`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutIcons {
+    /// Workspace with a single tiled window.
+    pub single: String,
+    /// Workspace with several tiled windows side by side.
+    pub multiple: String,
+}
+
+impl Default for LayoutIcons {
+    fn default() -> Self {
+        Self {
+            single: "window-symbolic".to_owned(),
+            multiple: "view-dual-symbolic".to_owned(),
+        }
+    }
+}
+
+/// Action of double-clicking a workspace button, see [`Config::double_click_action`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DoubleClickAction {
+    /// Double clicks are two single clicks.
+    #[default]
+    None,
+    /// Moves the focused window to the workspace and follows it there.
+    MoveWindowHere,
+    /// Turns the name into a text field right in the panel to rename the workspace, as long
+    /// as names are shown.
+    Rename,
+}
+
+/// Rounded background setting the applet apart from its neighbours, see
+/// [`Config::background`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppletBackground {
+    /// Colour as `#rrggbb`, the theme's button colour if unset or invalid.
+    pub color: Option<String>,
+    /// Opacity of the colour.
+    pub opacity: f32,
+    pub radius: f32,
+    /// Space between the background's edge and the buttons in pixels.
+    pub padding: u16,
+}
+
+impl Default for AppletBackground {
+    fn default() -> Self {
+        Self {
+            color: None,
+            opacity: 0.5,
+            radius: 8.0,
+            padding: 4,
+        }
+    }
+}
+
+/// Kinds of niri events to follow, see [`Config::events`]. Everything is followed by default.
+///
+/// Leaving out window events saves tracking every window, at the cost of window icons,
+/// counts and lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventCategories {
+    pub workspaces: bool,
+    pub windows: bool,
+}
+
+impl Default for EventCategories {
+    fn default() -> Self {
+        Self {
+            workspaces: true,
+            windows: true,
+        }
+    }
+}
+
+/// Easing of animations, see [`Config::animation_easing`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum EasingCurve {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// A damped spring with the given damping and stiffness, overshooting unless damped enough.
+    ///
+    /// Sizes overshoot with it, opacities stop at fully opaque. Both are bounded, see
+    /// [`MAX_SPRING_DAMPING`] and [`MAX_SPRING_STIFFNESS`].
+    Spring(f32, f32),
+}
+
+/// Largest damping and stiffness of [`EasingCurve::Spring`], past those its simulation
+/// becomes unstable or the spring is just a jump.
+pub const MAX_SPRING_DAMPING: f32 = 100.0;
+pub const MAX_SPRING_STIFFNESS: f32 = 2000.0;
+
+impl EasingCurve {
+    /// The curve with the spring's damping and stiffness within `0.0` and their maximum,
+    /// and `0.0` if they aren't numbers.
+    pub fn bounded(self) -> Self {
+        let bound = |value: f32, max: f32| match value.is_finite() {
+            true => value.clamp(0.0, max),
+            false => 0.0,
+        };
+        match self {
+            Self::Spring(damping, stiffness) => Self::Spring(
+                bound(damping, MAX_SPRING_DAMPING),
+                bound(stiffness, MAX_SPRING_STIFFNESS),
+            ),
+            curve => curve,
+        }
+    }
+}
+
+/// Workspace event that can show a notification, see [`Config::notify_on`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WorkspaceEvent {
+    /// A window opened on the workspace.
+    WindowOpened,
+    /// niri added the workspace.
+    WorkspaceCreated,
+}
+
+/// Action of scrolling over an indicator, see [`Config::window_total_scroll`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScrollAction {
+    #[default]
+    None,
+    /// Focuses the next or previous column on the focused workspace, wrapping around.
+    CycleWindows,
+    /// Focuses the workspace below or above on the focused output.
+    CycleWorkspaces,
+}
+
+impl ScrollAction {
+    /// The niri action of one scroll step, down or right if `forward`.
+    pub fn action(self, forward: bool) -> Option<Action> {
+        match (self, forward) {
+            (Self::None, _) => None,
+            (Self::CycleWindows, true) => Some(Action::FocusColumnRightOrFirst {}),
+            (Self::CycleWindows, false) => Some(Action::FocusColumnLeftOrLast {}),
+            (Self::CycleWorkspaces, true) => Some(Action::FocusWorkspaceDown {}),
+            (Self::CycleWorkspaces, false) => Some(Action::FocusWorkspaceUp {}),
+        }
+    }
+}
+
+/// Named workspace with apps, listed in the new workspace menu.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceTemplate {
+    /// Shown in the menu.
+    pub name: String,
+    /// Name of the created workspace, `{number}` is replaced by the lowest number that isn't
+    /// taken yet.
+    pub create_name: String,
+    /// Commands started on the new workspace through `sh -c`.
+    pub initial_apps: Vec<String>,
+}
+
+/// Button running a niri action, see [`Config::action_buttons`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActionButton {
+    /// Icon name, shown instead of the label if set.
+    pub icon: Option<String>,
+    pub label: String,
+    /// The action as niri's IPC encodes it, e.g. `{"FullscreenWindow":{}}`.
+    pub action: String,
+}
+
+impl ActionButton {
+    /// The parsed action, or `None` with a warning if it's not a valid niri action.
+    pub fn action(&self) -> Option<Action> {
+        match serde_json::from_str(&self.action) {
+            Ok(action) => Some(action),
+            Err(e) => {
+                eprintln!("skipping invalid action {:?}: {e}", self.action);
+                None
+            }
+        }
+    }
+}
+
+/// Corners of the workspace buttons.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ButtonShape {
+    /// Fully rounded ends, following the theme.
+    #[default]
+    Pill,
+    /// Slightly rounded corners.
+    Rounded,
+    /// Sharp corners.
+    Square,
+}
+
+/// Where an element goes relative to the workspace buttons.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Placement {
+    /// Before the first button.
+    Start,
+    /// After the last button.
+    #[default]
+    End,
+}
+
+/// Settings of [`Config`] that can differ per output. Unset fields use the base config.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputConfig {
+    pub indicator_style: Option<IndicatorStyle>,
+    pub segmented: Option<bool>,
+    pub neon_mode: Option<bool>,
+    pub stealth_mode: Option<bool>,
+    pub monochrome_mode: Option<bool>,
+    pub flat_mode: Option<bool>,
+    pub zen_mode: Option<bool>,
+    pub alt_click_closes_window: Option<bool>,
+    pub wrap_layout: Option<bool>,
+    pub hide_trailing_empty: Option<bool>,
+    pub show_layout_indicator: Option<bool>,
+    pub show_app_targets: Option<bool>,
+}
+
+impl Config {
+    /// Loads the config, falling back to defaults for anything missing or invalid.
+    ///
+    /// The returned handler is needed to write settings back. Errors are returned as text to
+    /// show the user, the config is usable regardless.
+    pub fn load(app_id: &str) -> (Option<cosmic_config::Config>, Self, Vec<String>) {
+        let handler = match cosmic_config::Config::new(app_id, Self::VERSION) {
+            Ok(handler) => handler,
+            Err(e) => return (None, Self::default(), vec![e.to_string()]),
+        };
+        let (mut config, mut errors) = match Self::get_entry(&handler) {
+            Ok(config) => (config, Vec::new()),
+            Err((errors, config)) => (config, errors.iter().map(ToString::to_string).collect()),
+        };
+        errors.extend(config.validate());
+        (Some(handler), config, errors)
+    }
+
+    /// Brings settings that would break the applet back within bounds, returning what was
+    /// wrong with them.
+    pub fn validate(&mut self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let easing = self.animation_easing.bounded();
+        if easing != self.animation_easing {
+            errors.push(format!(
+                "animation_easing: spring damping must be within 0 and {MAX_SPRING_DAMPING}, \
+                 stiffness within 0 and {MAX_SPRING_STIFFNESS}"
+            ));
+            self.animation_easing = easing;
+        }
+        errors
+    }
+
+    /// Key of `workspace` in [`Config::labels`]: its niri name, or else its output and index
+    /// like `DP-1:2`. Both stay the same when niri restarts.
+    pub fn label_key(workspace: &Workspace) -> String {
+        match &workspace.name {
+            Some(name) => name.clone(),
+            None => format!(
+                "{}:{}",
+                workspace.output.as_deref().unwrap_or_default(),
+                workspace.idx
+            ),
+        }
+    }
+
+    /// The label set for `workspace` in the applet, if there is one.
+    pub fn label(&self, workspace: &Workspace) -> Option<&str> {
+        self.labels
+            .get(&Self::label_key(workspace))
+            .map(String::as_str)
+    }
+
+    /// The config with the overrides for `output` applied.
+    pub fn for_output(mut self, output: &str) -> Self {
+        let Some(overrides) = self.outputs.get(output).cloned() else {
+            return self;
+        };
+        let OutputConfig {
+            indicator_style,
+            segmented,
+            neon_mode,
+            stealth_mode,
+            monochrome_mode,
+            flat_mode,
+            zen_mode,
+            alt_click_closes_window,
+            wrap_layout,
+            hide_trailing_empty,
+            show_layout_indicator,
+            show_app_targets,
+        } = overrides;
+        self.indicator_style = indicator_style.unwrap_or(self.indicator_style);
+        self.segmented = segmented.unwrap_or(self.segmented);
+        self.neon_mode = neon_mode.unwrap_or(self.neon_mode);
+        self.stealth_mode = stealth_mode.unwrap_or(self.stealth_mode);
+        self.monochrome_mode = monochrome_mode.unwrap_or(self.monochrome_mode);
+        self.flat_mode = flat_mode.unwrap_or(self.flat_mode);
+        self.zen_mode = zen_mode.unwrap_or(self.zen_mode);
+        self.alt_click_closes_window =
+            alt_click_closes_window.unwrap_or(self.alt_click_closes_window);
+        self.wrap_layout = wrap_layout.unwrap_or(self.wrap_layout);
+        self.hide_trailing_empty = hide_trailing_empty.unwrap_or(self.hide_trailing_empty);
+        self.show_layout_indicator = show_layout_indicator.unwrap_or(self.show_layout_indicator);
+        self.show_app_targets = show_app_targets.unwrap_or(self.show_app_targets);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn button(action: &str) -> ActionButton {
+        ActionButton {
+            action: action.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_action_buttons() {
+        assert!(matches!(
+            button(r#"{"FullscreenWindow":{}}"#).action(),
+            Some(Action::FullscreenWindow { id: None })
+        ));
+        assert!(matches!(
+            button(r#"{"CloseWindow":{"id":3}}"#).action(),
+            Some(Action::CloseWindow { id: Some(3) })
+        ));
+        assert!(button("fullscreen-window").action().is_none());
+        assert!(button(r#"{"NoSuchAction":{}}"#).action().is_none());
+    }
+
+    #[test]
+    fn labels_by_name_or_position() {
+        let unnamed = Workspace {
+            id: 5,
+            idx: 2,
+            name: None,
+            output: Some("DP-1".to_owned()),
+            is_active: false,
+            is_focused: false,
+            active_window_id: None,
+        };
+        let named = Workspace {
+            name: Some("chat".to_owned()),
+            ..unnamed.clone()
+        };
+        let config = Config {
+            labels: HashMap::from([
+                ("DP-1:2".to_owned(), "2️⃣".to_owned()),
+                ("chat".to_owned(), "💬".to_owned()),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(config.label(&unnamed), Some("2️⃣"));
+        assert_eq!(config.label(&named), Some("💬"));
+        let elsewhere = Workspace {
+            output: Some("HDMI-A-1".to_owned()),
+            ..unnamed
+        };
+        assert_eq!(config.label(&elsewhere), None);
+    }
+
+    #[test]
+    fn bounds_springs() {
+        let mut config = Config {
+            animation_easing: EasingCurve::Spring(-1.0, 1e9),
+            ..Default::default()
+        };
+        assert_eq!(config.validate().len(), 1);
+        assert_eq!(
+            config.animation_easing,
+            EasingCurve::Spring(0.0, MAX_SPRING_STIFFNESS)
+        );
+        assert!(config.validate().is_empty());
+    }
+}
@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::HashMap;
+
+/// Most workspaces remembered, older visits are forgotten first.
+const CAPACITY: usize = 50;
+
+/// Workspaces focused this session, to go back and forth through like browser history.
+#[derive(Debug, Default)]
+pub struct History {
+    /// Visited workspace ids, oldest first.
+    entries: Vec<u64>,
+    /// Index of the current workspace in `entries`.
+    position: usize,
+    /// Workspace a step back or forward is going to, whose focus isn't a new visit.
+    navigating: Option<u64>,
+}
+
+impl History {
+    /// Records that `id` got focus, dropping what was ahead after going back unless it's
+    /// the focus [`Self::back`] or [`Self::forward`] asked for.
+    pub fn visited(&mut self, id: u64) {
+        if self.navigating.take() == Some(id) {
+            return;
+        }
+        if self.entries.get(self.position) == Some(&id) {
+            return;
+        }
+        self.entries.truncate(self.position + 1);
+        self.entries.push(id);
+        if self.entries.len() > CAPACITY {
+            self.entries.remove(0);
+        }
+        self.position = self.entries.len() - 1;
+    }
+
+    /// Steps back to the last workspace before the current one that `exists`.
+    pub fn back(&mut self, exists: impl Fn(u64) -> bool) -> Option<u64> {
+        let index = self.back_index(exists)?;
+        Some(self.go(index))
+    }
+
+    /// Steps forward again to the next workspace after the current one that `exists`.
+    pub fn forward(&mut self, exists: impl Fn(u64) -> bool) -> Option<u64> {
+        let index = self.forward_index(exists)?;
+        Some(self.go(index))
+    }
+
+    pub fn can_go_back(&self, exists: impl Fn(u64) -> bool) -> bool {
+        self.back_index(exists).is_some()
+    }
+
+    pub fn can_go_forward(&self, exists: impl Fn(u64) -> bool) -> bool {
+        self.forward_index(exists).is_some()
+    }
+
+    /// Moves the history to the new ids in `ids`, forgetting workspaces that are gone.
+    pub fn remap(&mut self, ids: &HashMap<u64, u64>) {
+        let mut position = 0;
+        let mut entries = Vec::with_capacity(self.entries.len());
+        for (index, id) in self.entries.iter().enumerate() {
+            let Some(&id) = ids.get(id) else {
+                continue;
+            };
+            if index <= self.position {
+                position = entries.len();
+            }
+            entries.push(id);
+        }
+        self.entries = entries;
+        self.position = position;
+        self.navigating = None;
+    }
+
+    fn back_index(&self, exists: impl Fn(u64) -> bool) -> Option<usize> {
+        let earlier = self.entries.get(..self.position)?;
+        earlier.iter().rposition(|&id| exists(id))
+    }
+
+    fn forward_index(&self, exists: impl Fn(u64) -> bool) -> Option<usize> {
+        let later = self.entries.get(self.position + 1..)?;
+        let offset = later.iter().position(|&id| exists(id))?;
+        Some(self.position + 1 + offset)
+    }
+
+    fn go(&mut self, index: usize) -> u64 {
+        self.position = index;
+        let id = self.entries[index];
+        self.navigating = Some(id);
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn goes_back_and_forth() {
+        let mut history = History::default();
+        let all = |_| true;
+        assert!(!history.can_go_back(all));
+        for id in [1, 2, 3] {
+            history.visited(id);
+        }
+        assert_eq!(history.back(all), Some(2));
+        history.visited(2);
+        assert_eq!(history.back(all), Some(1));
+        history.visited(1);
+        assert!(!history.can_go_back(all));
+        assert_eq!(history.forward(all), Some(2));
+        history.visited(2);
+
+        // a new visit drops what was ahead
+        history.visited(4);
+        assert!(!history.can_go_forward(all));
+        assert_eq!(history.back(all), Some(2));
+    }
+
+    #[test]
+    fn skips_workspaces_that_are_gone() {
+        let mut history = History::default();
+        for id in [1, 2, 3] {
+            history.visited(id);
+        }
+        assert_eq!(history.back(|id| id != 2), Some(1));
+        history.visited(1);
+        assert!(!history.can_go_forward(|id| id == 1));
+        assert_eq!(history.forward(|id| id != 2), Some(3));
+    }
+
+    #[test]
+    fn forgets_the_oldest_visits() {
+        let mut history = History::default();
+        for id in 0..=CAPACITY as u64 {
+            history.visited(id);
+        }
+        assert_eq!(history.entries.len(), CAPACITY);
+        assert_eq!(history.entries[0], 1);
+        assert_eq!(history.position, CAPACITY - 1);
+    }
+}
@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use niri_ipc::Workspace;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// Runs `template` through `sh -c` in the background with the placeholders filled in for
+/// `workspace`, see [`expand`].
+///
+/// Anything the command writes to stderr is logged. Failing to spawn it is logged, too.
+pub fn run(template: &str, workspace: &Workspace) {
+    let command = expand(template, workspace);
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(&command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("failed to run activation command {command:?}: {e}");
+            return;
+        }
+    };
+    thread::spawn(move || {
+        if let Some(stderr) = child.stderr.take() {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                eprintln!("activation command: {line}");
+            }
+        }
+        match child.wait() {
+            Ok(status) if !status.success() => eprintln!("activation command failed: {status}"),
+            Ok(_) => {}
+            Err(e) => eprintln!("failed to wait for activation command: {e}"),
+        }
+    });
+}
+
+/// `template` with `{id}`, `{idx}`, `{name}` and `{output}` replaced by shell-quoted values of
+/// `workspace`. Unnamed workspaces and ones without output get empty strings.
+fn expand(template: &str, workspace: &Workspace) -> String {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = rest
+            .find('}')
+            .and_then(|end| Some((placeholder(&rest[1..end], workspace)?, end)));
+        match value {
+            Some((value, end)) => {
+                expanded.push_str(&value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                expanded.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Value of the placeholder called `name` for `workspace`, if there is one.
+fn placeholder(name: &str, workspace: &Workspace) -> Option<String> {
+    match name {
+        "id" => Some(workspace.id.to_string()),
+        "idx" => Some(workspace.idx.to_string()),
+        "name" => Some(quote(workspace.name.as_deref().unwrap_or_default())),
+        "output" => Some(quote(workspace.output.as_deref().unwrap_or_default())),
+        _ => None,
+    }
+}
+
+/// `value` in single quotes for `sh`.
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_placeholders() {
+        let workspace = Workspace {
+            id: 7,
+            idx: 2,
+            name: Some("it's {id}".to_owned()),
+            output: Some("DP-1".to_owned()),
+            is_active: true,
+            is_focused: true,
+            active_window_id: None,
+        };
+        assert_eq!(
+            expand(
+                "wallpaper {idx} {name} --output={output} # {id} {x}",
+                &workspace
+            ),
+            r"wallpaper 2 'it'\''s {id}' --output='DP-1' # 7 {x}"
+        );
+        let unnamed = Workspace {
+            name: None,
+            ..workspace
+        };
+        assert_eq!(expand("echo {name}", &unnamed), "echo ''");
+    }
+}
@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::widget::icon;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+
+/// Most app ids whose icons are kept around.
+const CAPACITY: usize = 256;
+
+/// Loaded app icons by app id, so `view()` doesn't search the icon theme every frame.
+///
+/// Searching the theme is slow, so icons are looked up in the background: apps asked for before
+/// their icon is loaded are collected with [`Self::take_missing`] and the results handed back to
+/// [`Self::loaded`]. Apps without an icon are remembered too. Everything is forgotten when the
+/// icon theme changes.
+#[derive(Debug, Default)]
+pub struct IconCache {
+    /// Icon theme the entries were loaded from.
+    theme: String,
+    entries: HashMap<String, Option<icon::Handle>>,
+    /// App ids in insertion order, oldest first, to evict once over [`CAPACITY`].
+    insertion_order: VecDeque<String>,
+    /// App ids asked for that aren't loaded yet.
+    missing: HashSet<String>,
+    /// App ids being looked up in the background.
+    loading: HashSet<String>,
+}
+
+impl IconCache {
+    /// Icon of the app with `app_id` in the current icon theme, `None` until it's loaded.
+    pub fn get(&mut self, app_id: &str) -> Option<icon::Handle> {
+        self.check_theme();
+        match self.entries.get(app_id) {
+            Some(handle) => handle.clone(),
+            None => {
+                self.want(app_id);
+                None
+            }
+        }
+    }
+
+    /// Asks for the icons of `app_ids` ahead of drawing them.
+    pub fn request<'a>(&mut self, app_ids: impl IntoIterator<Item = &'a str>) {
+        self.check_theme();
+        for app_id in app_ids {
+            if !self.entries.contains_key(app_id) {
+                self.want(app_id);
+            }
+        }
+    }
+
+    pub fn has_missing(&self) -> bool {
+        !self.missing.is_empty()
+    }
+
+    /// App ids to look up, from then on counted as loading.
+    pub fn take_missing(&mut self) -> Vec<String> {
+        let missing: Vec<String> = self.missing.drain().collect();
+        self.loading.extend(missing.iter().cloned());
+        missing
+    }
+
+    /// Icon theme lookups are done in, to pass on to [`Self::loaded`].
+    pub fn theme(&self) -> &str {
+        &self.theme
+    }
+
+    /// Stores the icon paths found in `theme`, unless the theme changed meanwhile.
+    pub fn loaded(&mut self, theme: &str, found: Vec<(String, Option<PathBuf>)>) {
+        self.check_theme();
+        for (app_id, path) in found {
+            self.loading.remove(&app_id);
+            if theme == self.theme {
+                self.insert(app_id, path.map(icon::from_path));
+            }
+        }
+    }
+
+    fn want(&mut self, app_id: &str) {
+        if !self.loading.contains(app_id) {
+            self.missing.insert(app_id.to_owned());
+        }
+    }
+
+    fn insert(&mut self, app_id: String, handle: Option<icon::Handle>) {
+        if self.entries.contains_key(&app_id) {
+            return;
+        }
+        if self.insertion_order.len() >= CAPACITY {
+            if let Some(oldest) = self.insertion_order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(app_id.clone(), handle);
+        self.insertion_order.push_back(app_id);
+    }
+
+    fn check_theme(&mut self) {
+        let theme = cosmic::icon_theme::default();
+        if theme != self.theme {
+            self.entries.clear();
+            self.insertion_order.clear();
+            // lookups in the old theme are dropped once they're done
+            self.missing.extend(self.loading.drain());
+            self.theme = theme;
+        }
+    }
+}
+
+/// Path of the icon of the app with `app_id` in the current icon theme.
+///
+/// Searches the theme on disk, so it's best kept off the UI thread.
+pub fn find(app_id: &str) -> Option<PathBuf> {
+    icon::from_name(app_id).path()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_icons_are_loaded_once() {
+        let mut cache = IconCache::default();
+        assert!(cache.get("firefox").is_none());
+        assert!(cache.get("unknown").is_none());
+        let mut missing = cache.take_missing();
+        missing.sort();
+        assert_eq!(missing, ["firefox", "unknown"]);
+
+        // still loading
+        assert!(cache.get("firefox").is_none());
+        assert!(!cache.has_missing());
+
+        let theme = cache.theme().to_owned();
+        let firefox = Some(PathBuf::from("/usr/share/icons/firefox.svg"));
+        cache.loaded(
+            &theme,
+            vec![("firefox".into(), firefox), ("unknown".into(), None)],
+        );
+        assert!(cache.get("firefox").is_some());
+        assert!(cache.get("unknown").is_none());
+        assert!(!cache.has_missing());
+    }
+
+    #[test]
+    fn evicts_oldest_entries() {
+        let mut cache = IconCache::default();
+        let app_ids: Vec<String> = (0..=CAPACITY).map(|i| i.to_string()).collect();
+        cache.request(app_ids.iter().map(String::as_str));
+        let found = cache
+            .take_missing()
+            .into_iter()
+            .map(|id| (id, None))
+            .collect();
+        let theme = cache.theme().to_owned();
+        cache.loaded(&theme, found);
+        assert_eq!(cache.entries.len(), CAPACITY);
+        assert_eq!(cache.insertion_order.len(), CAPACITY);
+    }
+}
@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::fs;
+
+/// Resident memory of process `pid` in bytes, from `/proc/{pid}/status`.
+pub fn resident(pid: i32) -> Option<u64> {
+    let status = fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+    parse_vm_rss(&status)
+}
+
+/// Rounded size like "1.2 GB" or "340 MB".
+pub fn format(bytes: u64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    const GB: f64 = 1024.0 * MB;
+    let bytes = bytes as f64;
+    match bytes >= GB {
+        true => format!("{:.1} GB", bytes / GB),
+        false => format!("{:.0} MB", bytes / MB),
+    }
+}
+
+/// The `VmRSS` line of a `/proc/{pid}/status` file, in bytes.
+fn parse_vm_rss(status: &str) -> Option<u64> {
+    let line = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?;
+    let kilobytes: u64 = line.trim().strip_suffix("kB")?.trim().parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_status() {
+        let status = "Name:\tfirefox\nVmPeak:\t 4000000 kB\nVmRSS:\t  524288 kB\nThreads:\t90\n";
+        assert_eq!(parse_vm_rss(status), Some(512 * 1024 * 1024));
+        assert_eq!(parse_vm_rss("Name:\tkthreadd\n"), None);
+        assert_eq!(format(512 * 1024 * 1024), "512 MB");
+        assert_eq!(format(1288 * 1024 * 1024), "1.3 GB");
+    }
+}
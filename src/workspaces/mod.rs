@@ -1,4 +1,21 @@
 mod app;
-mod niri;
+mod binds;
+mod cache;
+mod config;
+mod history;
+mod hook;
+mod icons;
+mod memory;
+pub(crate) mod niri;
+mod notify;
+mod order;
+mod power;
+mod render;
+pub mod state;
+mod style;
+mod usage;
 
+pub(crate) use app::Message;
 pub use app::WorkspacesApp;
+pub(crate) use config::{Config, EventCategories};
+pub(crate) use order::state_dir;
@@ -1,68 +1,863 @@
-use std::process;
+use std::path::PathBuf;
 
+// everything but the types shared with the mock only exists to talk to niri's socket
+#[cfg(not(feature = "mock_ipc"))]
+use std::collections::HashSet;
+#[cfg(not(feature = "mock_ipc"))]
+use std::fmt::Display;
+#[cfg(not(feature = "mock_ipc"))]
+use std::path::Path;
+#[cfg(not(feature = "mock_ipc"))]
+use std::time::{Duration, Instant};
+#[cfg(not(feature = "mock_ipc"))]
+use std::{env, fs, io, thread};
+
+#[cfg(not(feature = "mock_ipc"))]
 use cosmic::iced::futures::channel::mpsc::Sender;
+#[cfg(not(feature = "mock_ipc"))]
 use cosmic::iced::futures::{self, SinkExt};
+#[cfg(not(feature = "mock_ipc"))]
 use cosmic::iced::{futures::Stream, stream};
-use niri_ipc::socket::Socket;
-use niri_ipc::{Action, Event, Request, WorkspaceReferenceArg};
+#[cfg(not(feature = "mock_ipc"))]
+use niri_ipc::socket::{Socket, SOCKET_PATH_ENV};
+#[cfg(not(feature = "mock_ipc"))]
+use niri_ipc::{Action, Event, Request, Response, Workspace};
+#[cfg(not(feature = "mock_ipc"))]
 use std::sync::mpsc;
+#[cfg(not(feature = "mock_ipc"))]
+use tokio::task::JoinHandle;
 
+#[cfg(not(feature = "mock_ipc"))]
 use super::app::Message;
+#[cfg(not(feature = "mock_ipc"))]
+use super::config::EventCategories;
+#[cfg(not(feature = "mock_ipc"))]
+use crate::fl;
+
+/// niri release the niri-ipc dependency speaks the protocol of.
+#[cfg(not(feature = "mock_ipc"))]
+const SUPPORTED_VERSION: (u32, u32) = (25, 2);
+/// Interval of looking for niri's socket while it's not around.
+#[cfg(not(feature = "mock_ipc"))]
+const SOCKET_POLL: Duration = Duration::from_secs(5);
+/// Time niri gets to create its socket, e.g. when the panel starts first at login, before it's
+/// reported missing. Until then the socket is looked for every [`STARTUP_POLL`].
+#[cfg(not(feature = "mock_ipc"))]
+const STARTUP_GRACE: Duration = Duration::from_secs(30);
+#[cfg(not(feature = "mock_ipc"))]
+const STARTUP_POLL: Duration = Duration::from_millis(200);
+/// Bounds for the delay before reconnecting, doubling with every failed attempt. Kept short,
+/// niri restarting e.g. after an upgrade is back within a few seconds.
+#[cfg(not(feature = "mock_ipc"))]
+const MIN_BACKOFF: Duration = Duration::from_millis(250);
+#[cfg(not(feature = "mock_ipc"))]
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+/// Attempts at connecting to send an action, so a click while niri restarts isn't lost.
+#[cfg(not(feature = "mock_ipc"))]
+const ACTION_ATTEMPTS: u32 = 3;
+/// Minimum time between two log lines about skipped events, a chatty newer niri would
+/// otherwise flood the log.
+#[cfg(not(feature = "mock_ipc"))]
+const SKIP_LOG_INTERVAL: Duration = Duration::from_secs(60);
+/// niri counts workspace ids up from 1 every session, ids past this hint at stale ones or a
+/// bug, see [`IdCheck`].
+#[cfg(not(feature = "mock_ipc"))]
+const SUSPICIOUS_ID: u64 = 10_000;
+/// Largest plausible growth of the highest workspace id between two snapshots.
+#[cfg(not(feature = "mock_ipc"))]
+const MAX_ID_JUMP: u64 = 100;
+
+#[cfg(feature = "mock_ipc")]
+mod mock;
+#[cfg(feature = "mock_ipc")]
+pub use mock::sub;
 
-pub fn sub() -> impl Stream<Item = Message> {
-    let (sender, receiver) = mpsc::channel();
-    let (output_sender, output_receiver) = mpsc::channel();
-    tokio::task::spawn_blocking(move || apply_change(receiver));
+/// Events of niri's IPC as messages, limited to workspaces on `outputs` unless it's empty and
+/// to the `events` categories.
+///
+/// `discovery` tells where to look for niri's socket, see [`discover_socket_path`].
+#[cfg(not(feature = "mock_ipc"))]
+pub fn sub(
+    outputs: Vec<String>,
+    discovery: Discovery,
+    events: EventCategories,
+) -> impl Stream<Item = Message> {
     stream::channel(128, |mut output| async move {
-        output.send(Message::Ready(sender)).await.unwrap();
-        tokio::task::spawn_blocking(move || listen(output_receiver));
-        output_sender.send(output).unwrap();
+        let (sender, receiver) = mpsc::channel();
+        if output.send(Message::Ready(sender)).await.is_err() {
+            // the applet is already shutting down
+            return;
+        }
+        let errors = output.clone();
+        let actions_discovery = discovery.clone();
+        let actions =
+            tokio::task::spawn_blocking(move || apply_change(receiver, errors, actions_discovery));
+        let filter = OutputFilter::new(outputs);
+        let crashes = output.clone();
+        let listener =
+            tokio::task::spawn_blocking(move || listen(output, filter, discovery, events));
+        futures::join!(
+            watch(actions, crashes.clone(), |detail| Message::Error {
+                context: fl!("error-action"),
+                detail,
+            }),
+            watch(listener, crashes, |detail| {
+                Message::ConnectionStateChanged(ConnectionState::Error(detail))
+            }),
+        );
     })
 }
 
-fn listen(rx: mpsc::Receiver<Sender<Message>>) {
-    let socket = match Socket::connect() {
-        Ok(s) => s,
-        Err(e) => {
-            eprintln!("{e}");
-            // not wasting any ram
-            process::exit(1);
-        }
+/// Waits for `task` and tells the applet if it panicked, which would otherwise wait for it
+/// forever.
+#[cfg(not(feature = "mock_ipc"))]
+async fn watch(
+    task: JoinHandle<()>,
+    mut output: Sender<Message>,
+    message: impl FnOnce(String) -> Message,
+) {
+    let Err(e) = task.await else {
+        return;
     };
-    let mut event_stream = socket.send(Request::EventStream).unwrap().1;
-
-    let mut output = rx.recv().unwrap();
-
-    while let Ok(event) = event_stream() {
-        match event {
-            Event::WorkspacesChanged { workspaces } => {
-                futures::executor::block_on(async {
-                    output
-                        .send(Message::WorkspaceUpdate(workspaces))
-                        .await
-                        .unwrap()
-                });
+    if !e.is_panic() {
+        return;
+    }
+    let panic = e.into_panic();
+    let detail = match (panic.downcast_ref::<&str>(), panic.downcast_ref::<String>()) {
+        (Some(detail), _) => detail.to_string(),
+        (_, Some(detail)) => detail.clone(),
+        (None, None) => "unknown panic".to_owned(),
+    };
+    let _ = output.send(message(fl!("crashed", detail = detail))).await;
+}
+
+/// Stage of the connection to niri's event stream.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionState {
+    /// The event stream ended, reconnecting after a backoff.
+    Disconnected,
+    /// Waiting for niri's socket and subscribing to its events.
+    Connecting,
+    Connected,
+    /// Connecting failed with this error, retrying after a backoff.
+    Error(String),
+}
+
+#[cfg(not(feature = "mock_ipc"))]
+fn listen(
+    mut output: Sender<Message>,
+    mut filter: OutputFilter,
+    discovery: Discovery,
+    events: EventCategories,
+) {
+    let discovery = &discovery;
+    let sockets = sockets();
+    if discovery.session.is_none() && sockets.len() > 1 {
+        eprintln!("several niri instances are running, set niri_session to pick one of:");
+        for socket in sockets {
+            eprintln!("  {}", socket.display());
+        }
+    }
+    let mut backoff = MIN_BACKOFF;
+    loop {
+        let connecting = Message::ConnectionStateChanged(ConnectionState::Connecting);
+        if !send(&mut output, connecting) || !wait_for_socket(&mut output, discovery) {
+            return;
+        }
+        if let Ok(path) = discover_socket_path(discovery) {
+            eprintln!("connecting to niri at {}", path.display());
+        }
+        if let Some(niri) = unsupported_version(discovery) {
+            let (major, minor) = SUPPORTED_VERSION;
+            let message = Message::VersionMismatch {
+                niri,
+                supported: format!("{major}.{minor:02}"),
+            };
+            if !send(&mut output, message) {
+                return;
+            }
+        }
+        filter.warn_unknown_outputs(discovery);
+        let state = match subscribe(discovery) {
+            Ok(event_stream) => {
+                let connected = Message::ConnectionStateChanged(ConnectionState::Connected);
+                if !send(&mut output, connected) {
+                    return;
+                }
+                backoff = MIN_BACKOFF;
+                match forward_events(&mut output, &mut filter, events, event_stream) {
+                    Some(e) => {
+                        eprintln!("lost niri's event stream: {e}");
+                        ConnectionState::Disconnected
+                    }
+                    None => return,
+                }
+            }
+            Err(e) => ConnectionState::Error(e.to_string()),
+        };
+        if !send(&mut output, Message::ConnectionStateChanged(state)) {
+            return;
+        }
+        thread::sleep(backoff);
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+/// Waits until niri's socket exists, or returns `false` if the applet is gone or stopped
+/// listening, e.g. to retry right away.
+///
+/// The applet keeps showing that it's connecting during [`STARTUP_GRACE`] and connects as soon
+/// as the socket shows up. Past that, niri is reported missing and looked for less often.
+#[cfg(not(feature = "mock_ipc"))]
+fn wait_for_socket(output: &mut Sender<Message>, discovery: &Discovery) -> bool {
+    let start = Instant::now();
+    let mut missing = false;
+    while discover_socket_path(discovery).is_err() {
+        if output.is_closed() {
+            return false;
+        }
+        if start.elapsed() < STARTUP_GRACE {
+            thread::sleep(STARTUP_POLL);
+            continue;
+        }
+        if !missing {
+            missing = true;
+            if !send(output, Message::NiriMissing) {
+                return false;
+            }
+        }
+        thread::sleep(SOCKET_POLL);
+    }
+    true
+}
+
+#[cfg(not(feature = "mock_ipc"))]
+fn subscribe(discovery: &Discovery) -> io::Result<impl FnMut() -> io::Result<Event>> {
+    match connect(discovery)?.send(Request::EventStream)? {
+        (Ok(_), event_stream) => Ok(event_stream),
+        (Err(e), _) => Err(io::Error::other(e)),
+    }
+}
+
+/// Forwards events to the applet until the stream fails, returning the error.
+///
+/// Returns `None` once the applet is gone.
+#[cfg(not(feature = "mock_ipc"))]
+fn forward_events(
+    output: &mut Sender<Message>,
+    filter: &mut OutputFilter,
+    events: EventCategories,
+    mut event_stream: impl FnMut() -> io::Result<Event>,
+) -> Option<io::Error> {
+    let mut ids = IdCheck::default();
+    // when the last skipped event was logged and how many were skipped since
+    let mut last_logged: Option<Instant> = None;
+    let mut skipped = 0;
+    loop {
+        let event = match event_stream() {
+            Ok(event) => event,
+            // most likely an event this niri-ipc version doesn't know, the rest still works
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                let now = Instant::now();
+                if last_logged.map_or(true, |at| now - at >= SKIP_LOG_INTERVAL) {
+                    match skipped {
+                        0 => eprintln!("skipping unreadable event: {e}"),
+                        _ => eprintln!("skipping unreadable event: {e} ({skipped} more skipped)"),
+                    }
+                    last_logged = Some(now);
+                    skipped = 0;
+                } else {
+                    skipped += 1;
+                }
+                continue;
+            }
+            Err(e) => return Some(e),
+        };
+        let message = match event {
+            Event::WorkspacesChanged { .. }
+            | Event::WorkspaceActivated { .. }
+            | Event::WorkspaceActiveWindowChanged { .. }
+                if !events.workspaces =>
+            {
+                continue;
+            }
+            Event::WindowsChanged { .. }
+            | Event::WindowOpenedOrChanged { .. }
+            | Event::WindowClosed { .. }
+            | Event::WindowFocusChanged { .. }
+                if !events.windows =>
+            {
+                continue;
+            }
+            Event::WorkspacesChanged { mut workspaces } => {
+                for warning in ids.check(&workspaces) {
+                    eprintln!("{warning}");
+                }
+                filter.retain(&mut workspaces);
+                Message::WorkspaceUpdate(workspaces)
             }
             Event::WorkspaceActivated { id, focused } => {
-                futures::executor::block_on(async {
-                    output
-                        .send(Message::WorkspaceActivated { id, focused })
-                        .await
-                        .unwrap()
-                });
+                if !filter.passes(id) {
+                    continue;
+                }
+                Message::WorkspaceActivated { id, focused }
             }
-            _ => (),
+            Event::WorkspaceActiveWindowChanged {
+                workspace_id,
+                active_window_id,
+            } => {
+                if !filter.passes(workspace_id) {
+                    continue;
+                }
+                Message::WorkspaceActiveWindowChanged {
+                    workspace_id,
+                    active_window_id,
+                }
+            }
+            Event::WindowsChanged { windows } => Message::WindowsChanged(windows),
+            Event::WindowOpenedOrChanged { window } => Message::WindowOpenedOrChanged(window),
+            Event::WindowClosed { id } => Message::WindowClosed(id),
+            Event::WindowFocusChanged { id } => Message::WindowFocusChanged(id),
+            _ => continue,
+        };
+        if !send(output, message) {
+            return None;
+        }
+    }
+}
+
+/// Sends `message` to the applet, `false` if it's gone.
+#[cfg(not(feature = "mock_ipc"))]
+fn send(output: &mut Sender<Message>, message: Message) -> bool {
+    futures::executor::block_on(output.send(message)).is_ok()
+}
+
+#[cfg(not(feature = "mock_ipc"))]
+fn apply_change(
+    receiver: mpsc::Receiver<Action>,
+    mut errors: Sender<Message>,
+    discovery: Discovery,
+) {
+    while let Ok(action) = receiver.recv() {
+        let result =
+            connect_retrying(&discovery).and_then(|socket| socket.send(Request::Action(action)));
+        match result {
+            Ok((Ok(_), _)) => {}
+            Ok((Err(e), _)) => report(&mut errors, fl!("error-action"), e),
+            Err(e) => report(&mut errors, fl!("error-action"), e),
+        }
+    }
+}
+
+/// Connects to niri, trying again after a backoff up to [`ACTION_ATTEMPTS`] times.
+#[cfg(not(feature = "mock_ipc"))]
+fn connect_retrying(discovery: &Discovery) -> io::Result<Socket> {
+    let mut backoff = MIN_BACKOFF;
+    for _ in 1..ACTION_ATTEMPTS {
+        match connect(discovery) {
+            Ok(socket) => return Ok(socket),
+            Err(e) => eprintln!("failed to connect to niri, retrying: {e}"),
+        }
+        thread::sleep(backoff);
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+    connect(discovery)
+}
+
+/// Where to look for niri's socket, from the applet's config.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Discovery {
+    /// Part of the socket name of the niri instance to pick, like `wayland-2` or its pid.
+    pub session: Option<String>,
+    /// Socket to use unless `$NIRI_SOCKET` is set.
+    pub socket_path: Option<PathBuf>,
+}
+
+/// None of the candidates for niri's socket exist, most likely because the session isn't
+/// running niri.
+#[cfg(not(feature = "mock_ipc"))]
+#[derive(Debug)]
+pub struct DiscoveryError {
+    /// Paths looked at, in order.
+    pub tried: Vec<PathBuf>,
+}
+
+#[cfg(not(feature = "mock_ipc"))]
+impl Display for DiscoveryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "niri's socket was not found")?;
+        for (i, path) in self.tried.iter().enumerate() {
+            let separator = if i == 0 { ", tried " } else { ", " };
+            write!(f, "{separator}{}", path.display())?;
         }
+        Ok(())
     }
 }
 
-fn apply_change(receiver: mpsc::Receiver<u64>) {
-    while let Ok(id) = receiver.recv() {
-        let socket = Socket::connect().unwrap();
-        let _ = socket
-            .send(Request::Action(Action::FocusWorkspace {
-                reference: WorkspaceReferenceArg::Id(id),
-            }))
-            .unwrap();
+#[cfg(not(feature = "mock_ipc"))]
+impl std::error::Error for DiscoveryError {}
+
+/// niri's IPC socket, the first existing one of
+///
+/// 1. `$NIRI_SOCKET`,
+/// 2. the configured `socket_path`,
+/// 3. `$XDG_RUNTIME_DIR/niri/socket`,
+/// 4. the socket niri created in `$XDG_RUNTIME_DIR` for `$WAYLAND_DISPLAY`, or else the first
+///    one in there.
+///
+/// With a `session`, only the sockets in `$XDG_RUNTIME_DIR` whose name contains it are
+/// considered after the configured path, e.g. `wayland-2` or niri's pid, to pick one of
+/// several running niri instances.
+#[cfg(not(feature = "mock_ipc"))]
+fn discover_socket_path(discovery: &Discovery) -> Result<PathBuf, DiscoveryError> {
+    let session = discovery.session.as_deref();
+    let runtime_dir = env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+    let mut candidates = Vec::new();
+    if session.is_none() {
+        candidates.extend(env::var_os(SOCKET_PATH_ENV).map(PathBuf::from));
+    }
+    candidates.extend(discovery.socket_path.clone());
+    if session.is_none() {
+        candidates.extend(runtime_dir.map(|dir| dir.join("niri").join("socket")));
+    }
+    let display = env::var("WAYLAND_DISPLAY").ok();
+    let display = display.map(|display| format!(".{display}."));
+    let sockets = sockets();
+    let named = |part: Option<&str>| {
+        sockets.iter().find(|path| {
+            let name = path.file_name().and_then(|name| name.to_str());
+            name.is_some_and(|name| part.map_or(true, |part| name.contains(part)))
+        })
+    };
+    match session {
+        Some(session) => candidates.extend(named(Some(session)).cloned()),
+        None => {
+            candidates.extend(display.and_then(|display| named(Some(&display))).cloned());
+            candidates.extend(named(None).cloned());
+        }
+    }
+    match candidates.iter().find(|path| is_socket_candidate(path)) {
+        Some(path) => Ok(path.clone()),
+        None => Err(DiscoveryError { tried: candidates }),
+    }
+}
+
+/// Whether `path` exists and isn't a directory. Whether niri listens on it only shows when
+/// connecting.
+#[cfg(not(feature = "mock_ipc"))]
+fn is_socket_candidate(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|metadata| !metadata.is_dir())
+}
+
+/// The sockets of all niri instances in `$XDG_RUNTIME_DIR`.
+#[cfg(not(feature = "mock_ipc"))]
+fn sockets() -> Vec<PathBuf> {
+    let Some(runtime_dir) = env::var_os("XDG_RUNTIME_DIR") else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(runtime_dir) else {
+        return Vec::new();
+    };
+    let mut sockets: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter(|path| {
+            let name = path.file_name().and_then(|name| name.to_str());
+            name.is_some_and(|name| name.starts_with("niri.") && name.ends_with(".sock"))
+        })
+        .collect();
+    sockets.sort();
+    sockets
+}
+
+#[cfg(not(feature = "mock_ipc"))]
+fn connect(discovery: &Discovery) -> io::Result<Socket> {
+    match discover_socket_path(discovery) {
+        Ok(path) => Socket::connect_to(path),
+        Err(e) => Err(io::Error::new(io::ErrorKind::NotFound, e)),
+    }
+}
+
+/// Watches the workspace ids of one event stream for anomalies in niri's IPC.
+#[cfg(not(feature = "mock_ipc"))]
+#[derive(Debug, Default)]
+struct IdCheck {
+    /// Highest id of any snapshot so far.
+    max_seen: Option<u64>,
+}
+
+#[cfg(not(feature = "mock_ipc"))]
+impl IdCheck {
+    /// Warnings about the ids of a workspace snapshot: the first id past [`SUSPICIOUS_ID`] and
+    /// the highest id jumping by more than [`MAX_ID_JUMP`].
+    fn check(&mut self, workspaces: &[Workspace]) -> Vec<String> {
+        let Some(max) = workspaces.iter().map(|w| w.id).max() else {
+            return Vec::new();
+        };
+        let mut warnings = Vec::new();
+        let previous = self.max_seen.unwrap_or(0);
+        if max > SUSPICIOUS_ID && previous <= SUSPICIOUS_ID {
+            warnings.push(format!(
+                "niri reported workspace id {max}, which is unusually large: \
+                 these may be stale ids from a previous session or a niri bug"
+            ));
+        }
+        if self
+            .max_seen
+            .is_some_and(|previous| max > previous.saturating_add(MAX_ID_JUMP))
+        {
+            warnings.push(format!(
+                "niri's highest workspace id jumped from {previous} to {max} in one event"
+            ));
+        }
+        self.max_seen = Some(previous.max(max));
+        warnings
+    }
+}
+
+/// Drops workspace events of outputs the user isn't interested in before they reach the app.
+#[cfg(not(feature = "mock_ipc"))]
+struct OutputFilter {
+    /// Outputs to keep, all of them if empty.
+    outputs: Vec<String>,
+    /// Ids of the workspaces on `outputs` as of the last workspace list.
+    workspaces: HashSet<u64>,
+}
+
+#[cfg(not(feature = "mock_ipc"))]
+impl OutputFilter {
+    fn new(outputs: Vec<String>) -> Self {
+        Self {
+            outputs,
+            workspaces: HashSet::new(),
+        }
+    }
+
+    /// Logs filtered outputs niri doesn't know about, they still apply once connected.
+    fn warn_unknown_outputs(&self, discovery: &Discovery) {
+        if self.outputs.is_empty() {
+            return;
+        }
+        let Ok(socket) = connect(discovery) else {
+            return;
+        };
+        let Ok((Ok(Response::Outputs(known)), _)) = socket.send(Request::Outputs) else {
+            return;
+        };
+        for output in self.outputs.iter().filter(|o| !known.contains_key(*o)) {
+            eprintln!("output filter contains unknown output {output}");
+        }
+    }
+
+    fn retain(&mut self, workspaces: &mut Vec<Workspace>) {
+        if self.outputs.is_empty() {
+            return;
+        }
+        workspaces.retain(|w| {
+            w.output
+                .as_ref()
+                .is_some_and(|output| self.outputs.contains(output))
+        });
+        self.workspaces = workspaces.iter().map(|w| w.id).collect();
+    }
+
+    fn passes(&self, workspace_id: u64) -> bool {
+        self.outputs.is_empty() || self.workspaces.contains(&workspace_id)
+    }
+}
+
+/// niri's version, if it differs from [`SUPPORTED_VERSION`].
+#[cfg(not(feature = "mock_ipc"))]
+fn unsupported_version(discovery: &Discovery) -> Option<String> {
+    let socket = connect(discovery).ok()?;
+    let Ok(Response::Version(version)) = socket.send(Request::Version).ok()?.0 else {
+        return None;
+    };
+    (parse_version(&version) != Some(SUPPORTED_VERSION)).then_some(version)
+}
+
+/// Major and minor version of a niri version string like `25.02 (b94a5db)`.
+pub fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let mut numbers = version.split(|c: char| !c.is_ascii_digit());
+    let major = numbers.next()?.parse().ok()?;
+    let minor = numbers.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// Shows an error in the applet, unless it's already gone.
+#[cfg(not(feature = "mock_ipc"))]
+fn report(output: &mut Sender<Message>, context: String, detail: impl Display) {
+    let message = Message::Error {
+        context,
+        detail: detail.to_string(),
+    };
+    send(output, message);
+}
+
+#[cfg(all(test, not(feature = "mock_ipc")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_versions() {
+        assert_eq!(parse_version("25.02 (b94a5db)"), Some((25, 2)));
+        assert_eq!(parse_version("25.05.1 (unknown commit)"), Some((25, 5)));
+        assert_eq!(parse_version("unknown"), None);
+    }
+
+    fn workspaces(ids: &[u64]) -> Vec<Workspace> {
+        ids.iter()
+            .map(|&id| Workspace {
+                id,
+                idx: 1,
+                name: None,
+                output: None,
+                is_active: false,
+                is_focused: false,
+                active_window_id: None,
+            })
+            .collect()
+    }
+
+    /// Feeds `lines` through [`forward_events`] like niri-ipc's reader would, returning the
+    /// error ending the stream and the messages sent on.
+    fn forward(lines: Vec<Vec<u8>>, filter: &mut OutputFilter) -> (io::Error, Vec<Message>) {
+        let (mut output, mut messages) = futures::channel::mpsc::channel(lines.len());
+        let mut lines = lines.into_iter();
+        // niri-ipc turns serde errors into `InvalidData`, or `UnexpectedEof` for cut off lines
+        let event_stream = move || match lines.next() {
+            Some(line) => Ok(serde_json::from_slice::<Event>(&line)?),
+            None => Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+        };
+        let events = EventCategories::default();
+        let error = forward_events(&mut output, filter, events, event_stream)
+            .expect("the applet is still listening");
+        let messages = std::iter::from_fn(|| messages.try_next().ok().flatten()).collect();
+        (error, messages)
+    }
+
+    #[test]
+    fn flags_odd_workspace_ids() {
+        let mut ids = IdCheck::default();
+        // the first snapshot may start anywhere below the limit
+        assert!(ids.check(&workspaces(&[150, 151])).is_empty());
+        assert!(ids.check(&workspaces(&[150, 152])).is_empty());
+        assert_eq!(ids.check(&workspaces(&[150, 400])).len(), 1);
+        assert!(ids.check(&workspaces(&[])).is_empty());
+        assert_eq!(ids.check(&workspaces(&[20_000])).len(), 2);
+        assert!(ids.check(&workspaces(&[20_001])).is_empty());
+    }
+
+    #[test]
+    fn huge_workspace_ids_dont_overflow() {
+        let mut ids = IdCheck::default();
+        assert_eq!(ids.check(&workspaces(&[u64::MAX])).len(), 1);
+        assert!(ids.check(&workspaces(&[u64::MAX])).is_empty());
+    }
+
+    #[test]
+    fn skips_unknown_events() {
+        let lines = [
+            r#"{"WorkspaceActivated":{"id":1,"focused":true}}"#,
+            r#"{"FutureEvent":{"id":1,"shiny":true}}"#,
+            r#"{"WindowClosed":{"id":2}}"#,
+            r#"{"FutureEvent":{}}"#,
+            r#"{"WindowFocusChanged":{"id":null}}"#,
+        ];
+        let lines = lines.map(|line| line.as_bytes().to_vec()).to_vec();
+        let (error, messages) = forward(lines, &mut OutputFilter::new(Vec::new()));
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        let mut messages = messages.into_iter();
+        let mut next = || messages.next();
+        assert!(matches!(
+            next(),
+            Some(Message::WorkspaceActivated {
+                id: 1,
+                focused: true
+            })
+        ));
+        assert!(matches!(next(), Some(Message::WindowClosed(2))));
+        assert!(matches!(next(), Some(Message::WindowFocusChanged(None))));
+        assert!(next().is_none());
+    }
+
+    mod properties {
+        use super::*;
+        use crate::workspaces::state::WorkspaceState;
+        use niri_ipc::Window;
+        use proptest::prelude::*;
+        use proptest::sample::Index;
+
+        /// Mostly colliding ids, with the extremes mixed in.
+        fn arb_id() -> impl Strategy<Value = u64> {
+            prop_oneof![0..8u64, Just(u64::MAX), any::<u64>()]
+        }
+
+        fn arb_workspace() -> impl Strategy<Value = Workspace> {
+            let name = prop_oneof![Just(String::new()), ".{0,8}"];
+            let output = prop_oneof![Just("DP-1".to_owned()), Just(String::new())];
+            (
+                arb_id(),
+                any::<u8>(),
+                prop::option::of(name),
+                prop::option::of(output),
+                any::<(bool, bool)>(),
+                prop::option::of(arb_id()),
+            )
+                .prop_map(
+                    |(id, idx, name, output, (is_active, is_focused), active_window_id)| {
+                        Workspace {
+                            id,
+                            idx,
+                            name,
+                            output,
+                            is_active,
+                            is_focused,
+                            active_window_id,
+                        }
+                    },
+                )
+        }
+
+        fn arb_window() -> impl Strategy<Value = Window> {
+            (
+                arb_id(),
+                prop::option::of(".{0,8}"),
+                prop::option::of(".{0,8}"),
+                prop::option::of(any::<i32>()),
+                prop::option::of(arb_id()),
+                any::<(bool, bool)>(),
+            )
+                .prop_map(
+                    |(id, title, app_id, pid, workspace_id, (is_focused, is_floating))| Window {
+                        id,
+                        title,
+                        app_id,
+                        pid,
+                        workspace_id,
+                        is_focused,
+                        is_floating,
+                    },
+                )
+        }
+
+        fn arb_event() -> impl Strategy<Value = Event> {
+            prop_oneof![
+                prop::collection::vec(arb_workspace(), 0..8)
+                    .prop_map(|workspaces| Event::WorkspacesChanged { workspaces }),
+                (arb_id(), any::<bool>())
+                    .prop_map(|(id, focused)| Event::WorkspaceActivated { id, focused }),
+                (arb_id(), prop::option::of(arb_id())).prop_map(
+                    |(workspace_id, active_window_id)| Event::WorkspaceActiveWindowChanged {
+                        workspace_id,
+                        active_window_id,
+                    }
+                ),
+                prop::collection::vec(arb_window(), 0..8)
+                    .prop_map(|windows| Event::WindowsChanged { windows }),
+                arb_window().prop_map(|window| Event::WindowOpenedOrChanged { window }),
+                arb_id().prop_map(|id| Event::WindowClosed { id }),
+                prop::option::of(arb_id()).prop_map(|id| Event::WindowFocusChanged { id }),
+            ]
+        }
+
+        /// An event as niri would write it, or cut off, corrupted or broken in two.
+        fn arb_lines() -> impl Strategy<Value = Vec<Vec<u8>>> {
+            (arb_event(), 0..4u8, any::<Index>(), any::<u8>()).prop_map(
+                |(event, mutation, at, byte)| {
+                    let mut line = serde_json::to_vec(&event).unwrap();
+                    let at = at.index(line.len());
+                    match mutation {
+                        0 => vec![line],
+                        1 => vec![line[..at].to_vec()],
+                        2 => {
+                            line[at] = byte;
+                            vec![line]
+                        }
+                        _ => {
+                            let rest = line.split_off(at);
+                            vec![line, rest]
+                        }
+                    }
+                },
+            )
+        }
+
+        /// Applies `message` like the applet does.
+        fn fold(state: &mut WorkspaceState, message: Message) {
+            match message {
+                Message::WorkspaceUpdate(workspaces) => state.set_workspaces(workspaces),
+                Message::WorkspaceActivated { id, focused } => state.activated(id, focused),
+                Message::WorkspaceActiveWindowChanged {
+                    workspace_id,
+                    active_window_id,
+                } => state.active_window_changed(workspace_id, active_window_id),
+                Message::WindowsChanged(windows) => state.set_windows(windows),
+                Message::WindowOpenedOrChanged(window) => state.window_opened_or_changed(window),
+                Message::WindowClosed(id) => state.window_closed(id),
+                Message::WindowFocusChanged(id) => state.window_focus_changed(id),
+                _ => {}
+            }
+        }
+
+        fn check_invariants(state: &WorkspaceState) -> Result<(), TestCaseError> {
+            let ids: Vec<u64> = state.workspaces().iter().map(|w| w.id).collect();
+            prop_assert!(ids.windows(2).all(|w| w[0] < w[1]));
+            for workspace in state.workspaces() {
+                prop_assert!(state.workspace(workspace.id).is_some());
+                prop_assert_eq!(
+                    state.active_window(workspace.id),
+                    workspace.active_window_id
+                );
+            }
+            for window in state.windows() {
+                prop_assert_eq!(state.window(window.id).map(|w| w.id), Some(window.id));
+            }
+            let on_workspaces: usize = ids.iter().map(|&id| state.window_count(id)).sum();
+            prop_assert!(on_workspaces <= state.windows().count());
+            Ok(())
+        }
+
+        proptest! {
+            #[test]
+            fn arbitrary_bytes_are_skipped(
+                lines in prop::collection::vec(
+                    prop::collection::vec(any::<u8>(), 0..96),
+                    0..16,
+                ),
+            ) {
+                let count = lines.len();
+                let (_, messages) = forward(lines, &mut OutputFilter::new(Vec::new()));
+                prop_assert!(messages.len() <= count);
+                let mut state = WorkspaceState::default();
+                for message in messages {
+                    fold(&mut state, message);
+                    check_invariants(&state)?;
+                }
+            }
+
+            #[test]
+            fn odd_events_keep_state_consistent(
+                lines in prop::collection::vec(arb_lines(), 0..24),
+                filtered in any::<bool>(),
+            ) {
+                let lines: Vec<Vec<u8>> = lines.into_iter().flatten().collect();
+                let outputs = match filtered {
+                    true => vec!["DP-1".to_owned()],
+                    false => Vec::new(),
+                };
+                let mut filter = OutputFilter::new(outputs);
+                let (_, messages) = forward(lines, &mut filter);
+                let mut state = WorkspaceState::default();
+                for message in messages {
+                    fold(&mut state, message);
+                    check_invariants(&state)?;
+                    if filtered {
+                        let outputs = state.outputs();
+                        prop_assert!(outputs.iter().all(|&output| output == "DP-1"));
+                    }
+                }
+            }
+        }
     }
 }
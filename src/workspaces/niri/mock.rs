@@ -0,0 +1,73 @@
+//! Stand-in for niri's IPC for working on the applet outside of niri.
+//!
+//! Enabled with the `mock_ipc` feature. Shows `$NIRI_MOCK_WORKSPACES` workspaces (5 by
+//! default) on a single output and activates the next one every two seconds. Clicked
+//! workspaces are activated like niri would.
+
+use std::sync::mpsc;
+use std::time::Duration;
+use std::{env, thread};
+
+use cosmic::iced::futures::channel::mpsc::Sender;
+use cosmic::iced::futures::{self, SinkExt};
+use cosmic::iced::{futures::Stream, stream};
+use niri_ipc::{Action, Workspace, WorkspaceReferenceArg};
+
+use super::super::app::Message;
+use super::super::config::EventCategories;
+use super::{ConnectionState, Discovery};
+
+/// Interval the active workspace moves on at.
+const INTERVAL: Duration = Duration::from_secs(2);
+
+pub fn sub(
+    _outputs: Vec<String>,
+    _discovery: Discovery,
+    _events: EventCategories,
+) -> impl Stream<Item = Message> {
+    stream::channel(128, |mut output| async move {
+        let (sender, receiver) = mpsc::channel();
+        if output.send(Message::Ready(sender)).await.is_err() {
+            // the applet is already shutting down
+            return;
+        }
+        let connected = Message::ConnectionStateChanged(ConnectionState::Connected);
+        if output.send(connected).await.is_err() {
+            return;
+        }
+        tokio::task::spawn_blocking(move || cycle(output, receiver));
+    })
+}
+
+fn cycle(mut output: Sender<Message>, receiver: mpsc::Receiver<Action>) {
+    let count = env::var("NIRI_MOCK_WORKSPACES")
+        .ok()
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(5u8)
+        .max(1);
+    let mut active = 1;
+    loop {
+        let workspaces = (1..=count)
+            .map(|idx| Workspace {
+                id: u64::from(idx),
+                idx,
+                name: None,
+                output: Some("MOCK-1".to_owned()),
+                is_active: idx == active,
+                is_focused: idx == active,
+                active_window_id: None,
+            })
+            .collect();
+        if futures::executor::block_on(output.send(Message::WorkspaceUpdate(workspaces))).is_err() {
+            return;
+        }
+        active = match receiver.recv_timeout(INTERVAL) {
+            Ok(Action::FocusWorkspace {
+                reference: WorkspaceReferenceArg::Id(id),
+            }) => u8::try_from(id).unwrap_or(active),
+            Ok(_) => active,
+            Err(mpsc::RecvTimeoutError::Timeout) => active % count + 1,
+            Err(mpsc::RecvTimeoutError::Disconnected) => return,
+        };
+    }
+}
@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use notify_rust::Notification;
+use std::thread;
+
+/// Shows a desktop notification over D-Bus in the background.
+///
+/// Failures are logged, the applet works the same without a notification daemon.
+pub fn send(summary: &str, body: &str) {
+    let notification = Notification::new()
+        .appname("niri workspaces")
+        .summary(summary)
+        .body(body)
+        .finalize();
+    // talking to the notification daemon blocks
+    thread::spawn(move || {
+        if let Err(e) = notification.show() {
+            eprintln!("failed to show a notification: {e}");
+        }
+    });
+}
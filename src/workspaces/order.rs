@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use niri_ipc::Workspace;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::{env, fs, io};
+
+/// Display order of named workspaces, remembered across sessions.
+///
+/// niri hands out new ids every session, but names survive, so named workspaces are shown in
+/// the order they had last time and anything unnamed or new is appended in id order.
+#[derive(Debug, Default)]
+pub struct OrderMemory {
+    path: Option<PathBuf>,
+    preferred_order: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct OrderFile {
+    preferred_order: Vec<String>,
+}
+
+impl OrderMemory {
+    /// Reads `$XDG_STATE_HOME/cosmic-applet-niri/workspace_order.json`, if there is one.
+    pub fn load() -> Self {
+        let Some(path) = state_dir().map(|dir| dir.join("workspace_order.json")) else {
+            return Self::default();
+        };
+        let preferred_order = match fs::read_to_string(&path) {
+            Ok(json) => match serde_json::from_str::<OrderFile>(&json) {
+                Ok(file) => file.preferred_order,
+                Err(e) => {
+                    eprintln!("ignoring invalid {}: {e}", path.display());
+                    Vec::new()
+                }
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                eprintln!("failed to read {}: {e}", path.display());
+                Vec::new()
+            }
+        };
+        Self {
+            path: Some(path),
+            preferred_order,
+        }
+    }
+
+    /// Stable-sorts `workspaces` into the remembered order.
+    pub fn sort(&self, workspaces: &mut [&Workspace]) {
+        sort_by_names(workspaces, &self.preferred_order);
+    }
+
+    /// Remembers the order `workspaces` are displayed in, saving it if it changed.
+    ///
+    /// Names that aren't around right now keep their place after the current ones.
+    pub fn remember(&mut self, workspaces: &[&Workspace]) {
+        let mut order: Vec<String> = workspaces.iter().filter_map(|w| w.name.clone()).collect();
+        let missing: Vec<String> = self
+            .preferred_order
+            .iter()
+            .filter(|name| !order.contains(name))
+            .cloned()
+            .collect();
+        order.extend(missing);
+        if order == self.preferred_order {
+            return;
+        }
+        self.preferred_order = order;
+        if let Err(e) = self.save() {
+            eprintln!("failed to save workspace order: {e}");
+        }
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OrderFile {
+            preferred_order: self.preferred_order.clone(),
+        };
+        fs::write(path, serde_json::to_string_pretty(&file)?)
+    }
+}
+
+/// Stable-sorts `workspaces` with those named in `order` first, in that order.
+///
+/// Unnamed workspaces and names missing from `order` keep their relative order at the end.
+pub fn sort_by_names(workspaces: &mut [&Workspace], order: &[String]) {
+    workspaces.sort_by_key(|w| {
+        let name = w.name.as_ref();
+        let position = name.and_then(|name| order.iter().position(|n| n == name));
+        position.map_or((1, 0), |position| (0, position))
+    });
+}
+
+/// `$XDG_STATE_HOME/cosmic-applet-niri`, defaulting to `~/.local/state/cosmic-applet-niri`.
+pub fn state_dir() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".local/state"),
+    };
+    Some(base.join("cosmic-applet-niri"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(id: u64, name: Option<&str>) -> Workspace {
+        Workspace {
+            id,
+            idx: id as u8,
+            name: name.map(str::to_owned),
+            output: None,
+            is_active: false,
+            is_focused: false,
+            active_window_id: None,
+        }
+    }
+
+    #[test]
+    fn sorts_named_by_preference_then_rest_by_id() {
+        let memory = OrderMemory {
+            path: None,
+            preferred_order: vec!["mail".to_owned(), "web".to_owned(), "gone".to_owned()],
+        };
+        let workspaces = [
+            workspace(1, None),
+            workspace(2, Some("web")),
+            workspace(3, Some("new")),
+            workspace(4, Some("mail")),
+            workspace(5, None),
+        ];
+        let mut sorted: Vec<&Workspace> = workspaces.iter().collect();
+        memory.sort(&mut sorted);
+        let ids: Vec<u64> = sorted.iter().map(|w| w.id).collect();
+        assert_eq!(ids, [4, 2, 1, 3, 5]);
+    }
+
+    #[test]
+    fn manual_order_appends_unlisted() {
+        let workspaces = [
+            workspace(1, Some("web")),
+            workspace(2, None),
+            workspace(3, Some("chat")),
+            workspace(4, Some("mail")),
+        ];
+        let mut sorted: Vec<&Workspace> = workspaces.iter().collect();
+        sort_by_names(&mut sorted, &["mail".to_owned(), "web".to_owned()]);
+        let ids: Vec<u64> = sorted.iter().map(|w| w.id).collect();
+        assert_eq!(ids, [4, 1, 2, 3]);
+    }
+
+    #[test]
+    fn remember_keeps_absent_names() {
+        let mut memory = OrderMemory {
+            path: None,
+            preferred_order: vec!["mail".to_owned(), "gone".to_owned()],
+        };
+        let workspaces = [workspace(1, Some("web")), workspace(2, Some("mail"))];
+        memory.remember(&workspaces.iter().collect::<Vec<_>>());
+        assert_eq!(memory.preferred_order, ["web", "mail", "gone"]);
+    }
+}
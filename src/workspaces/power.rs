@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::iced::futures::channel::mpsc::Sender;
+use cosmic::iced::futures::{SinkExt, StreamExt};
+use cosmic::iced::{futures::Stream, stream};
+
+use super::app::Message;
+
+/// Profile of power-profiles-daemon while power saving is on.
+const POWER_SAVER: &str = "power-saver";
+
+#[zbus::proxy(
+    interface = "net.hadess.PowerProfiles",
+    default_service = "net.hadess.PowerProfiles",
+    default_path = "/net/hadess/PowerProfiles"
+)]
+trait PowerProfiles {
+    #[zbus(property)]
+    fn active_profile(&self) -> zbus::Result<String>;
+}
+
+/// Whether power saving is on as messages, first as it is and then whenever it changes.
+///
+/// Nothing is sent without power-profiles-daemon, power saving counts as off then.
+pub fn sub() -> impl Stream<Item = Message> {
+    stream::channel(4, |mut output| async move {
+        if let Err(e) = watch(&mut output).await {
+            eprintln!("failed to follow the power profile: {e}");
+        }
+    })
+}
+
+async fn watch(output: &mut Sender<Message>) -> zbus::Result<()> {
+    let connection = zbus::Connection::system().await?;
+    let profiles = PowerProfilesProxy::new(&connection).await?;
+    let mut changes = profiles.receive_active_profile_changed().await;
+    let mut saving = profiles.active_profile().await? == POWER_SAVER;
+    loop {
+        if output
+            .send(Message::PowerSavingChanged(saving))
+            .await
+            .is_err()
+        {
+            // the applet is shutting down
+            return Ok(());
+        }
+        let Some(change) = changes.next().await else {
+            return Ok(());
+        };
+        saving = change.get().await? == POWER_SAVER;
+    }
+}
@@ -0,0 +1,541 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::iced::mouse;
+use cosmic::iced::widget::canvas::{self, canvas};
+use cosmic::iced::widget::text::Wrapping;
+use cosmic::iced::widget::{mouse_area, Stack};
+use cosmic::iced::{Alignment, Color, Length, Padding, Point, Rectangle};
+use cosmic::widget::{self, icon, tooltip};
+use cosmic::Element;
+use serde::{Deserialize, Serialize};
+
+use super::app::Message;
+use super::config::ButtonShape;
+use super::style;
+
+/// Diameter of inactive dots, active ones are drawn larger.
+const DOT_SIZE: f32 = 6.0;
+/// Thickness of the bar indicators.
+const BAR_THICKNESS: f32 = 3.0;
+/// Size of app icons drawn on buttons.
+const ICON_SIZE: u16 = 12;
+/// Part of an icon the next one in a stack covers.
+const ICON_OVERLAP: f32 = 0.4;
+/// Size of the badges in the corner of buttons.
+const BADGE_SIZE: u16 = 8;
+/// Size of a square at least as large as one character of a label in the usual and in the large
+/// text, bounding labels while they grow or shrink.
+const LABEL_CELL: f32 = 20.0;
+const LARGE_LABEL_CELL: f32 = 32.0;
+/// Opacity of the accent of an active workspace on an output without focus.
+const UNFOCUSED_ACCENT: f32 = 0.5;
+
+/// How each workspace indicator is drawn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IndicatorStyle {
+    /// Blank button, highlighted when active.
+    #[default]
+    Button,
+    /// Small dot, larger and accent coloured when active.
+    Dot,
+    /// Bar along the panel edge.
+    Bar,
+    /// Button showing the workspace name, or its index when unnamed.
+    Label,
+    /// Button showing the workspace index, also when it's named.
+    Number,
+}
+
+/// App icon drawn on a button.
+pub enum AppIcon {
+    Themed(icon::Handle),
+    /// First letter of an app id without an icon, on a colour derived from the app id.
+    Letter {
+        letter: String,
+        hue: f32,
+    },
+}
+
+impl AppIcon {
+    /// `handle`, or a letter avatar for `app_id` if there's no icon.
+    pub fn new(app_id: &str, handle: Option<icon::Handle>) -> Self {
+        match handle {
+            Some(handle) => Self::Themed(handle),
+            None => Self::Letter {
+                letter: app_id
+                    .chars()
+                    .find(|c| c.is_alphanumeric())
+                    .unwrap_or('?')
+                    .to_uppercase()
+                    .collect(),
+                hue: style::hue(app_id),
+            },
+        }
+    }
+}
+
+/// Everything an indicator style needs to know about one workspace.
+pub struct Indicator {
+    /// Id of the workspace, which right clicks open the window list of.
+    pub id: u64,
+    /// Workspace name, or its index for unnamed workspaces.
+    pub label: String,
+    pub accessible_name: String,
+    pub active: bool,
+    /// Whether the workspace has keyboard focus, which only one active workspace has.
+    pub focused: bool,
+    /// Drawn outlined for a workspace without windows, see [`style::hollow`].
+    pub hollow: bool,
+    /// Icons drawn in place of the blank content, overlapping if there are several.
+    pub icons: Vec<AppIcon>,
+    /// Number of windows without an icon in `icons`, shown as "+N".
+    pub overflow: usize,
+    /// Length relative to the full size, `1.0` unless animating.
+    pub scale: f32,
+    /// Icon drawn small in the corner of the indicator, if any.
+    pub badge: Option<String>,
+    /// Number of windows drawn small in the opposite corner, if any.
+    pub window_count: Option<usize>,
+    /// Ripple spreading from where the indicator was pressed, if it was just pressed.
+    pub ripple: Option<RippleState>,
+    /// Opacity of the shimmer over an indicator whose switch is still pending.
+    pub shimmer: Option<f32>,
+    /// Usage of the workspace relative to the others for the heatmap, see [`Metrics::heatmap`].
+    pub heat: Option<f32>,
+    /// Text shown while hovering the indicator, if any.
+    pub tooltip: Option<String>,
+    pub on_press: Message,
+}
+
+/// Panel metrics and visual effects shared by all indicators.
+pub struct Metrics {
+    pub horizontal: bool,
+    /// Size across the panel.
+    pub thickness: f32,
+    /// Size along the panel at full scale.
+    pub length: f32,
+    /// Opacity of the glow around active indicators, when enabled.
+    pub glow: Option<f32>,
+    /// Opacity of everything the indicators draw, when fading, see [`style::fade`].
+    pub opacity: Option<f32>,
+    pub monochrome: bool,
+    /// Buttons let the panel show through, see [`style::flat`].
+    pub flat: bool,
+    /// Buttons are segments of one control, see [`style::segment`].
+    pub segmented: bool,
+    /// Labels are drawn large, see [`Config::presentation_mode`]. The thickness and length are
+    /// already scaled up for it.
+    ///
+    /// [`Config::presentation_mode`]: super::config::Config::presentation_mode
+    pub presentation: bool,
+    /// Colours of the least and most used workspace, when buttons show a heatmap.
+    pub heatmap: Option<(Color, Color)>,
+    /// Side of the indicators tooltips open on, away from the screen edge.
+    pub tooltip_position: tooltip::Position,
+    /// Indicators act when pressed instead of released, see [`Config::activate_on_press`].
+    ///
+    /// [`Config::activate_on_press`]: super::config::Config::activate_on_press
+    pub press_activates: bool,
+    pub shape: ButtonShape,
+}
+
+impl Metrics {
+    fn opacity(&self) -> f32 {
+        self.opacity.unwrap_or(1.0)
+    }
+
+    /// Width and height of an indicator at `scale`.
+    fn size(&self, scale: f32) -> (Length, Length) {
+        let along = Length::Fixed(self.length * scale);
+        let across = Length::Fixed(self.thickness);
+        match self.horizontal {
+            true => (along, across),
+            false => (across, along),
+        }
+    }
+}
+
+/// Thin line between the workspaces of two outputs.
+pub fn separator<'a>(metrics: &Metrics) -> Element<'a, Message> {
+    let (width, height) = match metrics.horizontal {
+        true => (1.0, metrics.thickness * 0.6),
+        false => (metrics.thickness * 0.6, 1.0),
+    };
+    widget::container(widget::Space::new(width, height))
+        .class(style::divider(metrics.opacity()))
+        .into()
+}
+
+/// Empty space the size of one indicator, reserving room for a workspace that isn't there.
+pub fn placeholder<'a>(metrics: &Metrics) -> Element<'a, Message> {
+    let (width, height) = metrics.size(1.0);
+    widget::Space::new(width, height).into()
+}
+
+impl IndicatorStyle {
+    pub fn render<'a>(self, indicator: Indicator, metrics: &Metrics) -> Element<'a, Message> {
+        let content = match self {
+            Self::Button => button(&indicator, metrics),
+            Self::Dot => dot(&indicator, metrics),
+            Self::Bar => bar(&indicator, metrics),
+            Self::Label | Self::Number => label(&indicator, metrics),
+        };
+        let content = match &indicator.badge {
+            Some(badge) => {
+                let badge = widget::icon::from_name(badge.as_str())
+                    .size(BADGE_SIZE)
+                    .icon();
+                let corner = widget::container(badge)
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .align_x(Alignment::End)
+                    .align_y(Alignment::Start);
+                Stack::with_children([content, corner.into()]).into()
+            }
+            None => content,
+        };
+        let content = match indicator.window_count {
+            Some(count) => {
+                let count = widget::text(count.to_string()).size(BADGE_SIZE + 1);
+                let corner = widget::container(count)
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .align_x(Alignment::End)
+                    .align_y(Alignment::End);
+                Stack::with_children([content, corner.into()]).into()
+            }
+            None => content,
+        };
+        let content = match indicator.shimmer {
+            Some(opacity) => {
+                let layer = widget::container(widget::Space::new(Length::Fill, Length::Fill))
+                    .class(style::shimmer(opacity * metrics.opacity(), metrics.shape));
+                Stack::with_children([content, layer.into()]).into()
+            }
+            None => content,
+        };
+        let content = match indicator.ripple {
+            Some(state) => ripple(content, state, metrics),
+            None => content,
+        };
+        let class = match metrics.opacity {
+            Some(opacity) => style::fade(self.class(&indicator, metrics), opacity),
+            None => self.class(&indicator, metrics),
+        };
+        let button = widget::button::custom(content)
+            .class(class)
+            .name(indicator.accessible_name);
+        let button: Element<'a, Message> = match metrics.press_activates {
+            // buttons only act on release, so the button has no message and leaves the press to
+            // the mouse area
+            true => mouse_area(button).on_press(indicator.on_press).into(),
+            false => button.on_press(indicator.on_press).into(),
+        };
+        match indicator.tooltip {
+            Some(text) => {
+                widget::tooltip(button, widget::text::body(text), metrics.tooltip_position).into()
+            }
+            None => button.into(),
+        }
+    }
+
+    fn class(self, indicator: &Indicator, metrics: &Metrics) -> cosmic::theme::Button {
+        if metrics.monochrome {
+            return style::monochrome(metrics.shape);
+        }
+        if let (Some(heat), Some((cold, hot))) = (indicator.heat, metrics.heatmap) {
+            if matches!(self, Self::Button | Self::Label | Self::Number) {
+                return style::heat(cold, hot, heat, indicator.active, metrics.shape);
+            }
+        }
+        if metrics.segmented && matches!(self, Self::Button | Self::Label | Self::Number) {
+            return style::segment(indicator.active);
+        }
+        if metrics.flat && matches!(self, Self::Button | Self::Label | Self::Number) {
+            return style::flat(indicator.active, metrics.shape);
+        }
+        let framed = matches!(self, Self::Button | Self::Label | Self::Number);
+        if framed && indicator.hollow && !indicator.active {
+            return style::hollow(metrics.shape);
+        }
+        if matches!(self, Self::Dot | Self::Bar) {
+            return match metrics.press_activates {
+                true => style::bare(),
+                false => cosmic::theme::Button::Text,
+            };
+        }
+        // the active workspaces of outputs without focus get a lighter accent
+        let accent = match indicator.active && !indicator.focused {
+            true => UNFOCUSED_ACCENT,
+            false => 1.0,
+        };
+        // the theme's classes have its corners, and look disabled on buttons without a message
+        let themed = metrics.shape == ButtonShape::Pill && !metrics.press_activates;
+        match (indicator.active, metrics.glow) {
+            (true, Some(strength)) => style::neon(strength, metrics.shape),
+            _ if !themed => style::faded(indicator.active, accent, metrics.shape),
+            (true, None) if indicator.focused => cosmic::theme::Button::Suggested,
+            (true, None) => style::faded(true, accent, metrics.shape),
+            (false, _) => cosmic::theme::Button::Standard,
+        }
+    }
+}
+
+/// Dot or bar of the given size, coloured like `indicator`.
+fn mark<'a>(
+    width: impl Into<Length>,
+    height: impl Into<Length>,
+    radius: f32,
+    indicator: &Indicator,
+    metrics: &Metrics,
+) -> Element<'a, Message> {
+    widget::container(widget::Space::new(width, height))
+        .class(style::mark(
+            indicator.active,
+            metrics.opacity(),
+            metrics.monochrome,
+            radius,
+        ))
+        .into()
+}
+
+/// Circle spreading from the point a button was pressed at while fading out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RippleState {
+    /// Point pressed, relative to the button.
+    pub center: Point,
+    /// Distance spread so far relative to the button's corner farthest from `center`, from
+    /// `0.0` to `1.0`.
+    pub radius: f32,
+    /// Opacity left, from `1.0` down to `0.0`.
+    pub alpha: f32,
+}
+
+/// `content` with its ripple drawn over it.
+fn ripple<'a>(
+    content: Element<'a, Message>,
+    state: RippleState,
+    metrics: &Metrics,
+) -> Element<'a, Message> {
+    let program = Ripple {
+        state,
+        opacity: metrics.opacity(),
+    };
+    let layer = canvas(program).width(Length::Fill).height(Length::Fill);
+    widget::container(Stack::with_children([content, layer.into()]))
+        .clip(true)
+        .into()
+}
+
+/// Draws a [`RippleState`] in the accent colour, faded to `opacity` with the rest of the button.
+struct Ripple {
+    state: RippleState,
+    opacity: f32,
+}
+
+impl canvas::Program<Message, cosmic::Theme> for Ripple {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &cosmic::Renderer,
+        theme: &cosmic::Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let RippleState {
+            center,
+            radius,
+            alpha,
+        } = self.state;
+        let corners = [
+            Point::ORIGIN,
+            Point::new(bounds.width, 0.0),
+            Point::new(0.0, bounds.height),
+            Point::new(bounds.width, bounds.height),
+        ];
+        let reach = corners
+            .into_iter()
+            .map(|corner| center.distance(corner))
+            .fold(0.0, f32::max);
+        let mut color: Color = theme.cosmic().accent_color().into();
+        color.a = 0.5 * alpha * self.opacity;
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        frame.fill(&canvas::Path::circle(center, radius * reach), color);
+        vec![frame.into_geometry()]
+    }
+}
+
+fn button<'a>(indicator: &Indicator, metrics: &Metrics) -> Element<'a, Message> {
+    if metrics.monochrome {
+        return dot(indicator, metrics);
+    }
+    let (width, height) = metrics.size(indicator.scale);
+    let icon = match indicator.icons.as_slice() {
+        [] => return widget::Space::new(width, height).into(),
+        [icon] => app_icon(icon, metrics.opacity()),
+        icons => {
+            // each icon is shifted along the panel by the visible part of the ones before it
+            let step = f32::from(ICON_SIZE) * (1.0 - ICON_OVERLAP);
+            let layers = icons.iter().enumerate().map(|(i, icon)| {
+                let padding = match metrics.horizontal {
+                    true => Padding::ZERO.left(step * i as f32),
+                    false => Padding::ZERO.top(step * i as f32),
+                };
+                widget::container(app_icon(icon, metrics.opacity()))
+                    .padding(padding)
+                    .into()
+            });
+            let stack: Element<'a, Message> = Stack::with_children(layers).into();
+            match (indicator.overflow, metrics.horizontal) {
+                (0, _) => stack,
+                (overflow, horizontal) => {
+                    let count = widget::text(format!("+{overflow}")).size(ICON_SIZE - 3);
+                    match horizontal {
+                        true => widget::row::with_children(vec![stack, count.into()])
+                            .spacing(2)
+                            .align_y(Alignment::Center)
+                            .into(),
+                        false => widget::column::with_children(vec![stack, count.into()])
+                            .spacing(2)
+                            .align_x(Alignment::Center)
+                            .into(),
+                    }
+                }
+            }
+        }
+    };
+    // stacks grow the button along the panel instead of getting cut off
+    let (width, height) = match (indicator.icons.len() > 1, metrics.horizontal) {
+        (true, true) => (Length::Shrink, height),
+        (true, false) => (width, Length::Shrink),
+        (false, _) => (width, height),
+    };
+    widget::container(icon)
+        .width(width)
+        .height(height)
+        .padding(match indicator.icons.len() > 1 {
+            true => 2,
+            false => 0,
+        })
+        .align_x(Alignment::Center)
+        .align_y(Alignment::Center)
+        .into()
+}
+
+/// `icon` drawn at `opacity`.
+fn app_icon<'a>(icon: &AppIcon, opacity: f32) -> Element<'a, Message> {
+    let size = Length::Fixed(ICON_SIZE.into());
+    match icon {
+        AppIcon::Themed(handle) if opacity >= 1.0 => {
+            widget::icon(handle.clone()).size(ICON_SIZE).into()
+        }
+        // the icon widget can't fade, the widgets it draws with can
+        AppIcon::Themed(handle) => match &handle.data {
+            icon::Data::Svg(svg) => cosmic::iced::widget::svg(svg.clone())
+                .width(size)
+                .height(size)
+                .opacity(opacity)
+                .into(),
+            icon::Data::Image(image) => cosmic::iced::widget::image(image.clone())
+                .width(size)
+                .height(size)
+                .opacity(opacity)
+                .into(),
+            // app icons are loaded from their files, never by name
+            icon::Data::Name(_) => widget::icon(handle.clone()).size(ICON_SIZE).into(),
+        },
+        AppIcon::Letter { letter, hue } => {
+            let letter = widget::text(letter.clone()).size(ICON_SIZE - 3);
+            widget::container(letter)
+                .width(size)
+                .height(size)
+                .align_x(Alignment::Center)
+                .align_y(Alignment::Center)
+                .class(style::avatar(*hue, opacity))
+                .into()
+        }
+    }
+}
+
+fn dot<'a>(indicator: &Indicator, metrics: &Metrics) -> Element<'a, Message> {
+    let (width, height) = metrics.size(indicator.scale);
+    let size = match indicator.active && !metrics.monochrome {
+        true => DOT_SIZE + 2.0,
+        false => DOT_SIZE,
+    };
+    widget::container(mark(size, size, size / 2.0, indicator, metrics))
+        .width(width)
+        .height(height)
+        .align_x(Alignment::Center)
+        .align_y(Alignment::Center)
+        .into()
+}
+
+fn bar<'a>(indicator: &Indicator, metrics: &Metrics) -> Element<'a, Message> {
+    let (width, height) = metrics.size(indicator.scale);
+    let radius = BAR_THICKNESS / 2.0;
+    let container = match metrics.horizontal {
+        true => widget::container(mark(
+            Length::Fill,
+            BAR_THICKNESS,
+            radius,
+            indicator,
+            metrics,
+        ))
+        .align_y(Alignment::End),
+        false => widget::container(mark(
+            BAR_THICKNESS,
+            Length::Fill,
+            radius,
+            indicator,
+            metrics,
+        ))
+        .align_x(Alignment::End),
+    };
+    container.width(width).height(height).into()
+}
+
+fn label<'a>(indicator: &Indicator, metrics: &Metrics) -> Element<'a, Message> {
+    let (_, height) = metrics.size(indicator.scale);
+    let (text, padding, cell) = match metrics.presentation {
+        true => (
+            widget::text::title3(indicator.label.clone()),
+            8,
+            LARGE_LABEL_CELL,
+        ),
+        false => (widget::text::body(indicator.label.clone()), 4, LABEL_CELL),
+    };
+    // cut off while it grows or shrinks instead of breaking into lines
+    let text = text.wrapping(Wrapping::None);
+    let label: Element<'a, Message> = match metrics.horizontal {
+        true => widget::container(text)
+            .height(height)
+            .padding([0, padding])
+            .align_y(Alignment::Center)
+            .into(),
+        // as wide as the panel, or wider for a long or large label instead of cutting it off
+        false => widget::column::with_children(vec![
+            widget::Space::with_width(Length::Fixed(metrics.thickness)).into(),
+            text.into(),
+        ])
+        .align_x(Alignment::Center)
+        .into(),
+    };
+    if indicator.scale >= 1.0 {
+        return label;
+    }
+    // the label's length is only known once it's laid out, so it's scaled from a length it
+    // surely fits in, growing to its own length a bit before the animation ends
+    let container = widget::container(label).clip(true);
+    let full = match metrics.horizontal {
+        true => indicator.label.chars().count() as f32 * cell + 2.0 * f32::from(padding),
+        false => cell,
+    };
+    match metrics.horizontal {
+        true => container.max_width(full * indicator.scale),
+        false => container.max_height(full * indicator.scale),
+    }
+    .into()
+}
@@ -0,0 +1,620 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use niri_ipc::{Action, Window, Workspace, WorkspaceReferenceArg};
+use std::collections::HashMap;
+use std::sync::mpsc;
+
+/// Workspace and window state mirrored from niri, kept independent of the COSMIC runtime.
+#[derive(Debug, Default)]
+pub struct WorkspaceState {
+    /// Sorted by id.
+    workspaces: Vec<Workspace>,
+    windows: HashMap<u64, Window>,
+    /// Whether niri listed the windows, which it doesn't unless window events are followed.
+    windows_known: bool,
+}
+
+impl WorkspaceState {
+    /// All workspaces of all outputs, sorted by id.
+    pub fn workspaces(&self) -> &[Workspace] {
+        &self.workspaces
+    }
+
+    /// Workspaces on `output` in niri's order, or those of every output grouped by output.
+    pub fn sorted_for_output(&self, output: Option<&str>) -> Vec<&Workspace> {
+        let mut workspaces: Vec<&Workspace> = self
+            .workspaces
+            .iter()
+            .filter(|w| output.is_none() || w.output.as_deref() == output)
+            .collect();
+        workspaces.sort_by(|a, b| (&a.output, a.idx).cmp(&(&b.output, b.idx)));
+        workspaces
+    }
+
+    /// Names of the outputs that have workspaces, sorted.
+    pub fn outputs(&self) -> Vec<&str> {
+        let mut outputs: Vec<&str> = self
+            .workspaces
+            .iter()
+            .filter_map(|w| w.output.as_deref())
+            .collect();
+        outputs.sort_unstable();
+        outputs.dedup();
+        outputs
+    }
+
+    pub fn workspace(&self, id: u64) -> Option<&Workspace> {
+        self.workspaces.iter().find(|w| w.id == id)
+    }
+
+    /// The workspace with keyboard focus.
+    pub fn focused(&self) -> Option<&Workspace> {
+        self.workspaces.iter().find(|w| w.is_focused)
+    }
+
+    pub fn windows(&self) -> impl Iterator<Item = &Window> {
+        self.windows.values()
+    }
+
+    /// Windows on `workspace_id`, in no particular order.
+    pub fn windows_on(&self, workspace_id: u64) -> impl Iterator<Item = &Window> {
+        self.windows
+            .values()
+            .filter(move |w| w.workspace_id == Some(workspace_id))
+    }
+
+    pub fn window_count(&self, workspace_id: u64) -> usize {
+        self.windows_on(workspace_id).count()
+    }
+
+    /// Number of windows on `workspace_id` that aren't floating.
+    pub fn tiled_window_count(&self, workspace_id: u64) -> usize {
+        self.windows_on(workspace_id)
+            .filter(|w| !w.is_floating)
+            .count()
+    }
+
+    /// Number of floating windows on `workspace_id`.
+    pub fn floating_window_count(&self, workspace_id: u64) -> usize {
+        self.windows_on(workspace_id)
+            .filter(|w| w.is_floating)
+            .count()
+    }
+
+    pub fn window(&self, id: u64) -> Option<&Window> {
+        self.windows.get(&id)
+    }
+
+    /// The window with keyboard focus.
+    pub fn focused_window(&self) -> Option<&Window> {
+        self.windows.values().find(|w| w.is_focused)
+    }
+
+    /// Replaces everything with a full snapshot from niri.
+    ///
+    /// Should niri ever report an id twice, only the first workspace with it is kept so that
+    /// id lookups stay unambiguous.
+    pub fn set_workspaces(&mut self, mut workspaces: Vec<Workspace>) {
+        workspaces.sort_by_key(|a| a.id);
+        let len = workspaces.len();
+        workspaces.dedup_by_key(|a| a.id);
+        if workspaces.len() != len {
+            eprintln!(
+                "ignoring {} workspaces with duplicate ids",
+                len - workspaces.len()
+            );
+        }
+        self.workspaces = workspaces;
+    }
+
+    fn contains(&self, id: u64) -> bool {
+        self.workspaces.binary_search_by_key(&id, |w| w.id).is_ok()
+    }
+
+    /// Applies a `WorkspaceActivated` event. Unknown ids are ignored.
+    ///
+    /// niri keeps an active workspace per output, so only the others on the same output become
+    /// inactive. Focus only moves away from other outputs when `focused`.
+    pub fn activated(&mut self, id: u64, focused: bool) {
+        let Some(output) = self.workspace(id).map(|w| w.output.clone()) else {
+            return;
+        };
+        for workspace in self.workspaces.iter_mut() {
+            if workspace.id == id {
+                workspace.is_active = true;
+                workspace.is_focused = focused;
+            } else if workspace.output == output {
+                workspace.is_active = false;
+                workspace.is_focused = false;
+            } else if focused {
+                workspace.is_focused = false;
+            }
+        }
+    }
+
+    /// Applies a `WorkspaceActiveWindowChanged` event.
+    pub fn active_window_changed(&mut self, workspace_id: u64, active_window_id: Option<u64>) {
+        if let Some(workspace) = self.workspaces.iter_mut().find(|w| w.id == workspace_id) {
+            workspace.active_window_id = active_window_id;
+        }
+    }
+
+    /// The window that has focus on `workspace_id` (or would get it when switching there).
+    pub fn active_window(&self, workspace_id: u64) -> Option<u64> {
+        let index = self
+            .workspaces
+            .binary_search_by_key(&workspace_id, |w| w.id)
+            .ok()?;
+        self.workspaces[index].active_window_id
+    }
+
+    /// Replaces all windows with a full `WindowsChanged` snapshot.
+    pub fn set_windows(&mut self, windows: Vec<Window>) {
+        self.windows = windows.into_iter().map(|w| (w.id, w)).collect();
+        self.windows_known = true;
+    }
+
+    /// Drops all windows once niri stops reporting them.
+    pub fn forget_windows(&mut self) {
+        self.windows.clear();
+        self.windows_known = false;
+    }
+
+    /// Whether `workspace_id` is known to have no windows. Without a window list from niri
+    /// every workspace counts as occupied.
+    pub fn is_empty(&self, workspace_id: u64) -> bool {
+        self.windows_known && self.window_count(workspace_id) == 0
+    }
+
+    /// Applies a `WindowOpenedOrChanged` event.
+    pub fn window_opened_or_changed(&mut self, window: Window) {
+        if window.is_focused {
+            for other in self.windows.values_mut() {
+                other.is_focused = false;
+            }
+        }
+        self.windows.insert(window.id, window);
+    }
+
+    /// Applies a `WindowClosed` event.
+    pub fn window_closed(&mut self, id: u64) {
+        self.windows.remove(&id);
+    }
+
+    /// Applies a `WindowFocusChanged` event.
+    pub fn window_focus_changed(&mut self, id: Option<u64>) {
+        for window in self.windows.values_mut() {
+            window.is_focused = Some(window.id) == id;
+        }
+    }
+
+    /// Whether `workspace` is the empty workspace niri always keeps last on each output.
+    pub fn is_trailing_empty(&self, workspace: &Workspace) -> bool {
+        workspace.active_window_id.is_none()
+            && self
+                .workspaces
+                .iter()
+                .filter(|w| w.output == workspace.output)
+                .all(|w| w.idx <= workspace.idx)
+    }
+
+    /// Asks niri to activate `id` and optimistically marks it active until niri confirms.
+    ///
+    /// Nothing changes if the request can't be sent. Unknown ids are not sent at all.
+    pub fn request_activation(
+        &mut self,
+        id: u64,
+        sender: &mpsc::Sender<Action>,
+    ) -> Result<(), mpsc::SendError<Action>> {
+        if !self.contains(id) {
+            return Ok(());
+        }
+        sender.send(Action::FocusWorkspace {
+            reference: WorkspaceReferenceArg::Id(id),
+        })?;
+        let output = self.workspace(id).and_then(|w| w.output.clone());
+        for workspace in self.workspaces.iter_mut() {
+            if workspace.output == output {
+                workspace.is_active = workspace.id == id;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// New ids of the workspaces in `old` that are still around in `new`, which niri renumbers
+/// when it restarts.
+///
+/// Named workspaces are matched by output and name, unnamed ones by output and index.
+pub fn matching_ids(old: &[Workspace], new: &[Workspace]) -> HashMap<u64, u64> {
+    old.iter()
+        .filter_map(|old| {
+            let new = new.iter().find(|new| {
+                new.output == old.output
+                    && match (&old.name, &new.name) {
+                        (Some(old), Some(new)) => old == new,
+                        (None, None) => new.idx == old.idx,
+                        _ => false,
+                    }
+            })?;
+            Some((old.id, new.id))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(id: u64, idx: u8) -> Workspace {
+        Workspace {
+            id,
+            idx,
+            name: None,
+            output: Some("DP-1".to_owned()),
+            is_active: false,
+            is_focused: false,
+            active_window_id: None,
+        }
+    }
+
+    fn window(id: u64, workspace_id: u64) -> Window {
+        Window {
+            id,
+            title: None,
+            app_id: None,
+            pid: None,
+            workspace_id: Some(workspace_id),
+            is_focused: false,
+            is_floating: false,
+        }
+    }
+
+    fn state(ids: &[u64]) -> WorkspaceState {
+        let mut state = WorkspaceState::default();
+        let workspaces = ids.iter().map(|&id| workspace(id, id as u8)).collect();
+        state.set_workspaces(workspaces);
+        state
+    }
+
+    fn active(state: &WorkspaceState) -> Vec<u64> {
+        let active = state.workspaces().iter().filter(|w| w.is_active);
+        active.map(|w| w.id).collect()
+    }
+
+    fn focus_requested(receiver: &mpsc::Receiver<Action>, id: u64) -> bool {
+        matches!(
+            receiver.try_recv(),
+            Ok(Action::FocusWorkspace {
+                reference: WorkspaceReferenceArg::Id(requested),
+            }) if requested == id
+        )
+    }
+
+    #[test]
+    fn matches_renumbered_workspaces() {
+        let named = |id, idx, name: &str| Workspace {
+            name: Some(name.to_owned()),
+            ..workspace(id, idx)
+        };
+        let old = [named(1, 1, "web"), workspace(2, 2), workspace(3, 3)];
+        let new = [workspace(11, 1), named(12, 2, "web"), workspace(13, 3)];
+        let ids = matching_ids(&old, &new);
+        assert_eq!(ids.get(&1), Some(&12));
+        // the unnamed workspace that was second is gone, the named one took its place
+        assert_eq!(ids.get(&2), None);
+        assert_eq!(ids.get(&3), Some(&13));
+    }
+
+    #[test]
+    fn update_sorts_by_id() {
+        let state = state(&[3, 1, 2]);
+        let ids: Vec<u64> = state.workspaces().iter().map(|w| w.id).collect();
+        assert_eq!(ids, [1, 2, 3]);
+    }
+
+    #[test]
+    fn activated_known_id() {
+        let mut state = state(&[1, 2, 3]);
+        state.activated(2, true);
+        assert_eq!(active(&state), [2]);
+        assert!(state.workspaces()[1].is_focused);
+
+        state.activated(3, false);
+        assert_eq!(active(&state), [3]);
+        assert!(state.workspaces().iter().all(|w| !w.is_focused));
+    }
+
+    #[test]
+    fn activated_keeps_other_outputs_active() {
+        let on = |id, output: &str| Workspace {
+            output: Some(output.to_owned()),
+            ..workspace(id, id as u8)
+        };
+        let mut state = WorkspaceState::default();
+        state.set_workspaces(vec![on(1, "DP-1"), on(2, "DP-1"), on(3, "HDMI-1")]);
+        state.activated(1, false);
+        state.activated(3, true);
+        assert_eq!(active(&state), [1, 3]);
+
+        state.activated(2, true);
+        assert_eq!(active(&state), [2, 3]);
+        assert!(!state.workspaces()[2].is_focused);
+
+        let (sender, _receiver) = mpsc::channel();
+        state.request_activation(1, &sender).unwrap();
+        assert_eq!(active(&state), [1, 3]);
+    }
+
+    #[test]
+    fn activated_unknown_id_is_ignored() {
+        let mut state = state(&[1, 2, 3]);
+        state.activated(1, true);
+        state.activated(42, true);
+        assert_eq!(active(&state), [1]);
+        assert!(state.workspaces()[0].is_focused);
+    }
+
+    #[test]
+    fn request_activation_is_optimistic() {
+        let mut state = state(&[1, 2, 3]);
+        state.activated(1, true);
+        let (sender, receiver) = mpsc::channel();
+
+        state.request_activation(3, &sender).unwrap();
+        assert!(focus_requested(&receiver, 3));
+        assert_eq!(active(&state), [3]);
+        // focus only moves once niri confirms the switch
+        assert!(state.workspaces()[0].is_focused);
+    }
+
+    #[test]
+    fn request_activation_send_failure_keeps_state() {
+        let mut state = state(&[1, 2, 3]);
+        state.activated(1, true);
+        let (sender, receiver) = mpsc::channel();
+        drop(receiver);
+
+        assert!(state.request_activation(3, &sender).is_err());
+        assert_eq!(active(&state), [1]);
+    }
+
+    #[test]
+    fn full_update_overrides_activation() {
+        let mut state = state(&[1, 2, 3]);
+        state.activated(2, true);
+
+        let mut snapshot = vec![workspace(1, 1), workspace(2, 2), workspace(3, 3)];
+        snapshot[0].is_active = true;
+        snapshot[0].is_focused = true;
+        state.set_workspaces(snapshot);
+        assert_eq!(active(&state), [1]);
+        assert!(state.workspaces()[0].is_focused);
+
+        // later activations apply on top of the new snapshot
+        state.activated(3, true);
+        assert_eq!(active(&state), [3]);
+    }
+
+    #[test]
+    fn active_window_tracking() {
+        let mut state = state(&[1, 2]);
+        state.active_window_changed(2, Some(10));
+        state.active_window_changed(3, Some(11));
+        assert_eq!(state.active_window(1), None);
+        assert_eq!(state.active_window(2), Some(10));
+        assert_eq!(state.active_window(3), None);
+
+        state.active_window_changed(2, None);
+        assert_eq!(state.active_window(2), None);
+    }
+
+    #[test]
+    fn trailing_empty_per_output() {
+        let mut snapshot = vec![workspace(1, 1), workspace(2, 2), workspace(3, 3)];
+        snapshot.extend([workspace(4, 1), workspace(5, 2)]);
+        for workspace in &mut snapshot[3..] {
+            workspace.output = Some("DP-2".to_owned());
+        }
+        snapshot[0].active_window_id = Some(10);
+        snapshot[3].active_window_id = Some(11);
+        snapshot[4].active_window_id = Some(12);
+        let mut state = WorkspaceState::default();
+        state.set_workspaces(snapshot);
+
+        let trailing: Vec<u64> = state
+            .workspaces()
+            .iter()
+            .filter(|w| state.is_trailing_empty(w))
+            .map(|w| w.id)
+            .collect();
+        // workspace 2 is empty but not last, DP-2's last workspace has a window
+        assert_eq!(trailing, [3]);
+    }
+
+    #[test]
+    fn window_counts_follow_events() {
+        let mut state = state(&[1, 2]);
+        state.set_windows(vec![window(10, 1), window(11, 1), window(12, 2)]);
+        assert_eq!(state.window_count(1), 2);
+        assert_eq!(state.window_count(2), 1);
+
+        // moved to another workspace
+        state.window_opened_or_changed(window(11, 2));
+        assert_eq!(state.window_count(1), 1);
+        assert_eq!(state.window_count(2), 2);
+
+        state.window_closed(12);
+        state.window_closed(99);
+        assert_eq!(state.window_count(2), 1);
+
+        state.set_windows(Vec::new());
+        assert_eq!(state.window_count(1), 0);
+    }
+
+    #[test]
+    fn emptiness_needs_a_window_list() {
+        let mut state = state(&[1, 2]);
+        assert!(!state.is_empty(1));
+        state.set_windows(vec![window(10, 1)]);
+        assert!(!state.is_empty(1));
+        assert!(state.is_empty(2));
+        state.forget_windows();
+        assert!(!state.is_empty(2));
+    }
+
+    #[test]
+    fn floating_counts_follow_toggles() {
+        let mut state = state(&[1]);
+        let mut floating = window(11, 1);
+        floating.is_floating = true;
+        state.set_windows(vec![window(10, 1), floating]);
+        assert_eq!(state.tiled_window_count(1), 1);
+        assert_eq!(state.floating_window_count(1), 1);
+
+        // tiled again
+        state.window_opened_or_changed(window(11, 1));
+        assert_eq!(state.tiled_window_count(1), 2);
+        assert_eq!(state.floating_window_count(1), 0);
+    }
+
+    #[test]
+    fn window_focus() {
+        let mut state = state(&[1]);
+        state.set_windows(vec![window(10, 1), window(11, 1)]);
+        state.window_focus_changed(Some(10));
+        assert_eq!(state.focused_window().map(|w| w.id), Some(10));
+
+        let mut opened = window(12, 1);
+        opened.is_focused = true;
+        state.window_opened_or_changed(opened);
+        assert_eq!(state.focused_window().map(|w| w.id), Some(12));
+        assert_eq!(state.windows().filter(|w| w.is_focused).count(), 1);
+
+        state.window_focus_changed(None);
+        assert!(state.focused_window().is_none());
+    }
+
+    #[test]
+    fn sorted_for_output_uses_idx() {
+        let mut snapshot = vec![workspace(1, 2), workspace(2, 1), workspace(3, 1)];
+        snapshot[2].output = Some("DP-2".to_owned());
+        let mut state = WorkspaceState::default();
+        state.set_workspaces(snapshot);
+
+        let ids = |output| -> Vec<u64> {
+            state
+                .sorted_for_output(output)
+                .iter()
+                .map(|w| w.id)
+                .collect()
+        };
+        assert_eq!(ids(Some("DP-1")), [2, 1]);
+        assert_eq!(ids(Some("DP-2")), [3]);
+        assert_eq!(ids(None), [2, 1, 3]);
+        assert_eq!(state.outputs(), ["DP-1", "DP-2"]);
+    }
+
+    #[test]
+    fn adversarial_ids() {
+        let mut state = WorkspaceState::default();
+        let mut duplicate = workspace(7, 9);
+        duplicate.is_active = true;
+        let snapshot = vec![
+            workspace(u64::MAX, 1),
+            workspace(7, 2),
+            workspace(0, 3),
+            duplicate,
+            workspace(u64::MAX, 4),
+        ];
+        state.set_workspaces(snapshot);
+        let ids: Vec<(u64, u8)> = state.workspaces().iter().map(|w| (w.id, w.idx)).collect();
+        assert_eq!(ids, [(0, 3), (7, 2), (u64::MAX, 1)]);
+
+        state.activated(u64::MAX, true);
+        assert_eq!(active(&state), [u64::MAX]);
+        state.activated(u64::MAX - 1, true);
+        assert_eq!(active(&state), [u64::MAX]);
+
+        let (sender, receiver) = mpsc::channel();
+        state.request_activation(1, &sender).unwrap();
+        assert!(receiver.try_recv().is_err());
+        assert_eq!(active(&state), [u64::MAX]);
+
+        state.request_activation(0, &sender).unwrap();
+        assert!(focus_requested(&receiver, 0));
+        assert_eq!(active(&state), [0]);
+    }
+
+    #[test]
+    fn activation_after_workspace_removed() {
+        let mut state = state(&[1, 2, 3]);
+        state.activated(2, true);
+        state.set_workspaces(vec![workspace(1, 1), workspace(3, 2)]);
+        state.activated(2, true);
+        assert!(active(&state).is_empty());
+    }
+
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn arb_workspace() -> impl Strategy<Value = Workspace> {
+            (0..16u64, any::<u8>(), any::<bool>(), any::<bool>()).prop_map(
+                |(id, idx, is_active, is_focused)| Workspace {
+                    is_active,
+                    is_focused,
+                    ..workspace(id, idx)
+                },
+            )
+        }
+
+        fn arb_snapshot() -> impl Strategy<Value = Vec<Workspace>> {
+            prop::collection::vec(arb_workspace(), 0..24)
+        }
+
+        fn ids(state: &WorkspaceState) -> Vec<u64> {
+            state.workspaces().iter().map(|w| w.id).collect()
+        }
+
+        proptest! {
+            #[test]
+            fn ids_are_sorted_and_unique(snapshot in arb_snapshot()) {
+                let mut state = WorkspaceState::default();
+                state.set_workspaces(snapshot);
+                let ids = ids(&state);
+                prop_assert!(ids.windows(2).all(|w| w[0] < w[1]));
+            }
+
+            #[test]
+            fn order_is_independent_of_input_order(
+                (snapshot, shuffled) in arb_snapshot()
+                    .prop_flat_map(|s| (Just(s.clone()), Just(s).prop_shuffle()))
+            ) {
+                let mut a = WorkspaceState::default();
+                a.set_workspaces(snapshot);
+                let mut b = WorkspaceState::default();
+                b.set_workspaces(shuffled);
+                prop_assert_eq!(ids(&a), ids(&b));
+            }
+
+            #[test]
+            fn known_activation_focuses_exactly_one(
+                snapshot in arb_snapshot(),
+                events in prop::collection::vec((0..20u64, any::<bool>()), 1..8),
+            ) {
+                let mut state = WorkspaceState::default();
+                state.set_workspaces(snapshot);
+                for (id, focused) in events {
+                    let known = state.contains(id);
+                    state.activated(id, focused);
+                    if known {
+                        prop_assert_eq!(active(&state), [id]);
+                        let focused_count = state.workspaces().iter().filter(|w| w.is_focused).count();
+                        prop_assert_eq!(focused_count, usize::from(focused));
+                    }
+                }
+            }
+        }
+    }
+}
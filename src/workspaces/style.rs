@@ -0,0 +1,415 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::cosmic_theme::palette::{Hsv, Hsva, IntoColor, Srgb, Srgba};
+use cosmic::iced::border::Radius;
+use cosmic::iced::widget::container;
+use cosmic::iced::{Background, Border, Color};
+use cosmic::theme::{Button, Container};
+use cosmic::widget::button::{self, Catalog};
+use std::rc::Rc;
+
+use super::config::ButtonShape;
+
+/// Suggested-looking button with an outline glowing in the fully saturated accent colour.
+///
+/// `strength` is the opacity of the glow, in `0.0..=1.0`.
+pub fn neon(strength: f32, shape: ButtonShape) -> Button {
+    custom(move |theme| {
+        let cosmic = theme.cosmic();
+        let mut glow: Hsva = cosmic.accent_color().into_color();
+        glow.saturation = 1.0;
+        let glow: Srgba = glow.into_color();
+
+        let mut style = button::Style::new();
+        style.background = Some(Background::Color(cosmic.accent_button.base.into()));
+        style.border_radius = radius(theme, shape);
+        style.outline_width = 2.0;
+        style.outline_color = Color {
+            a: strength,
+            ..Color::from(glow)
+        };
+        style
+    })
+}
+
+/// Plain suggested or standard button with its background faded to `opacity`.
+pub fn faded(active: bool, opacity: f32, shape: ButtonShape) -> Button {
+    custom(move |theme| {
+        let cosmic = theme.cosmic();
+        let component = match active {
+            true => &cosmic.accent_button,
+            false => &cosmic.button,
+        };
+        let mut background = Color::from(component.base);
+        background.a *= opacity;
+
+        let mut style = button::Style::new();
+        style.background = Some(Background::Color(background));
+        style.border_radius = radius(theme, shape);
+        style
+    })
+}
+
+/// `class` with everything it draws faded to `opacity`: background, outlines, text and icons.
+pub fn fade(class: Button, opacity: f32) -> Button {
+    let class = Rc::new(class);
+    let (active, disabled, hovered) = (class.clone(), class.clone(), class.clone());
+    Button::Custom {
+        active: Box::new(move |focused, theme| {
+            fade_style(theme.active(focused, false, &active), opacity, theme)
+        }),
+        disabled: Box::new(move |theme| fade_style(theme.disabled(&disabled), opacity, theme)),
+        hovered: Box::new(move |focused, theme| {
+            fade_style(theme.hovered(focused, false, &hovered), opacity, theme)
+        }),
+        pressed: Box::new(move |focused, theme| {
+            fade_style(theme.pressed(focused, false, &class), opacity, theme)
+        }),
+    }
+}
+
+fn fade_style(mut style: button::Style, opacity: f32, theme: &cosmic::Theme) -> button::Style {
+    let fade = |mut color: Color| {
+        color.a *= opacity;
+        color
+    };
+    // unset colours would be taken from the panel, which doesn't fade
+    let on = Color::from(theme.cosmic().background.on);
+    style.text_color = Some(fade(style.text_color.unwrap_or(on)));
+    style.icon_color = Some(fade(style.icon_color.unwrap_or(on)));
+    if let Some(Background::Color(color)) = &mut style.background {
+        *color = fade(*color);
+    }
+    style.border_color = fade(style.border_color);
+    style.outline_color = fade(style.outline_color);
+    style
+}
+
+/// Passes `opacity` on to the text inside that has no colour of its own, see [`fade`].
+pub fn faded_text(opacity: f32) -> Container<'static> {
+    Container::custom(move |theme| {
+        let mut color = Color::from(theme.cosmic().background.on);
+        color.a *= opacity;
+        container::Style {
+            text_color: Some(color),
+            ..Default::default()
+        }
+    })
+}
+
+/// Button without a background, looking the same when it's disabled.
+pub fn bare() -> Button {
+    custom(|_| button::Style::new())
+}
+
+/// Button coloured between `cold` and `hot` by `heat` in `0.0..=1.0`, outlined in the accent
+/// colour when `active`.
+///
+/// The configured colours don't follow the theme, so the label is black or white depending on
+/// the background instead of the theme's text colour, staying legible in light and dark mode.
+pub fn heat(cold: Color, hot: Color, heat: f32, active: bool, shape: ButtonShape) -> Button {
+    custom(move |theme| {
+        let cosmic = theme.cosmic();
+        let mix = |cold: f32, hot: f32| cold + (hot - cold) * heat;
+        let background =
+            Color::from_rgb(mix(cold.r, hot.r), mix(cold.g, hot.g), mix(cold.b, hot.b));
+
+        let mut style = button::Style::new();
+        style.background = Some(Background::Color(background));
+        style.text_color = Some(readable_on(background));
+        style.icon_color = Some(readable_on(background));
+        style.border_radius = radius(theme, shape);
+        if active {
+            style.outline_width = 2.0;
+            style.outline_color = cosmic.accent_color().into();
+        }
+        style
+    })
+}
+
+/// Black or white, whichever reads better on `background`.
+fn readable_on(background: Color) -> Color {
+    let luminance = 0.2126 * background.r + 0.7152 * background.g + 0.0722 * background.b;
+    match luminance > 0.5 {
+        true => Color::BLACK,
+        false => Color::WHITE,
+    }
+}
+
+/// Colour written as `#rrggbb`.
+pub fn parse_hex(hex: &str) -> Option<Color> {
+    let hex = hex.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some(Color::from_rgb8(channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Flat black button, see [`mark`].
+pub fn monochrome(shape: ButtonShape) -> Button {
+    custom(move |theme| {
+        let mut style = button::Style::new();
+        style.background = Some(Background::Color(Color::BLACK));
+        style.border_radius = radius(theme, shape);
+        style
+    })
+}
+
+/// Outlined button whose fill is as translucent as the panel, solid in the accent colour
+/// when `active`.
+pub fn flat(active: bool, shape: ButtonShape) -> Button {
+    custom(move |theme| {
+        let cosmic = theme.cosmic();
+        let mut style = button::Style::new();
+        if active {
+            style.background = Some(Background::Color(cosmic.accent_button.base.into()));
+        } else {
+            let mut fill = Color::from(cosmic.button.base);
+            fill.a *= 0.3 * cosmic.background.base.alpha;
+            style.background = Some(Background::Color(fill));
+            style.border_width = 1.0;
+            style.border_color = cosmic.background.divider.into();
+        }
+        style.border_radius = radius(theme, shape);
+        style
+    })
+}
+
+/// Outline of a button without a fill, for a workspace that has no windows.
+pub fn hollow(shape: ButtonShape) -> Button {
+    custom(move |theme| {
+        let cosmic = theme.cosmic();
+        let mut style = button::Style::new();
+        style.border_width = 1.0;
+        style.border_color = cosmic.background.divider.into();
+        style.border_radius = radius(theme, shape);
+        style
+    })
+}
+
+/// Square button inside a [`pill`], filled with the accent colour when `active`.
+pub fn segment(active: bool) -> Button {
+    custom(move |theme| {
+        let cosmic = theme.cosmic();
+        let mut style = button::Style::new();
+        if active {
+            style.background = Some(Background::Color(cosmic.accent_button.base.into()));
+        }
+        style.border_radius = 0.0.into();
+        style
+    })
+}
+
+/// Background of the whole applet in `color`, or the theme's button colour, at `opacity`.
+pub fn background(color: Option<Color>, opacity: f32, radius: f32) -> Container<'static> {
+    Container::custom(move |theme| {
+        let mut color = color.unwrap_or_else(|| theme.cosmic().button.base.into());
+        color.a *= opacity;
+        container::Style {
+            background: Some(Background::Color(color)),
+            border: Border {
+                radius: radius.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    })
+}
+
+/// Rounded background joining segment buttons into one control, at `opacity`.
+pub fn pill(shape: ButtonShape, opacity: f32) -> Container<'static> {
+    Container::custom(move |theme| {
+        let mut color = Color::from(theme.cosmic().button.base);
+        color.a *= opacity;
+        container::Style {
+            background: Some(Background::Color(color)),
+            border: Border {
+                radius: radius(theme, shape),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    })
+}
+
+/// Line between the segments of a [`pill`], at `opacity`.
+pub fn divider(opacity: f32) -> Container<'static> {
+    Container::custom(move |theme| {
+        let mut color = Color::from(theme.cosmic().background.divider);
+        color.a *= opacity;
+        container::Style {
+            background: Some(Background::Color(color)),
+            ..Default::default()
+        }
+    })
+}
+
+/// Dot or bar drawn inside an indicator: accent coloured when `active`, dimmed otherwise.
+///
+/// White when `monochrome` is set, see [`monochrome`].
+pub fn mark(active: bool, opacity: f32, monochrome: bool, radius: f32) -> Container<'static> {
+    Container::custom(move |theme| {
+        let mut color = match (monochrome, active) {
+            (true, _) => Color::WHITE,
+            (false, true) => theme.cosmic().accent_color().into(),
+            (false, false) => theme.cosmic().background.on.into(),
+        };
+        if !active {
+            color.a = match monochrome {
+                true => monochrome_dim(theme),
+                false => 0.4,
+            };
+        }
+        color.a *= opacity;
+        container::Style {
+            background: Some(Background::Color(color)),
+            border: Border {
+                radius: radius.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    })
+}
+
+/// Veil over a button whose switch is pending, in the text colour at `opacity`.
+pub fn shimmer(opacity: f32, shape: ButtonShape) -> Container<'static> {
+    Container::custom(move |theme| {
+        let mut color: Color = theme.cosmic().background.on.into();
+        color.a = opacity;
+        container::Style {
+            background: Some(Background::Color(color)),
+            border: Border {
+                radius: radius(theme, shape),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    })
+}
+
+/// Rounded square in a colour of `hue`, behind the letter of an app without an icon, both at
+/// `opacity`.
+pub fn avatar(hue: f32, opacity: f32) -> Container<'static> {
+    Container::custom(move |_| {
+        let background: Srgb = Hsv::new(hue, 0.55, 0.7).into_color();
+        let mut background = Color::from(background);
+        background.a *= opacity;
+        container::Style {
+            text_color: Some(Color {
+                a: opacity,
+                ..Color::WHITE
+            }),
+            background: Some(Background::Color(background)),
+            border: Border {
+                radius: 3.0.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    })
+}
+
+/// Hue in degrees derived from `seed`, the same for the same seed in every session.
+pub fn hue(seed: &str) -> f32 {
+    // FNV-1a, std's hasher is randomly seeded
+    let hash = seed.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    });
+    (hash % 360) as f32
+}
+
+/// Opacity of the white dots of inactive workspaces in monochrome mode, which stand out less
+/// next to a light panel than on a dark one.
+fn monochrome_dim(theme: &cosmic::Theme) -> f32 {
+    match theme.cosmic().is_dark {
+        true => 0.4,
+        false => 0.6,
+    }
+}
+
+/// Corner radius of buttons in `shape`.
+fn radius(theme: &cosmic::Theme, shape: ButtonShape) -> Radius {
+    match shape {
+        ButtonShape::Pill => theme.cosmic().corner_radii.radius_xl.into(),
+        ButtonShape::Rounded => theme.cosmic().corner_radii.radius_s.into(),
+        ButtonShape::Square => 0.0.into(),
+    }
+}
+
+/// Uses the same style for every interaction state of the button.
+fn custom<F>(style: F) -> Button
+where
+    F: Fn(&cosmic::Theme) -> button::Style + Copy + 'static,
+{
+    Button::Custom {
+        active: Box::new(move |_, theme| style(theme)),
+        disabled: Box::new(move |theme| style(theme)),
+        hovered: Box::new(move |_, theme| style(theme)),
+        pressed: Box::new(move |_, theme| style(theme)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Opacity of `background`, none counting as transparent.
+    fn alpha(background: Option<Background>) -> f32 {
+        match background {
+            Some(Background::Color(color)) => color.a,
+            _ => 0.0,
+        }
+    }
+
+    #[test]
+    fn fading_out_hides_everything() {
+        let theme = cosmic::Theme::dark();
+        let classes = [
+            Button::Standard,
+            Button::Suggested,
+            Button::Text,
+            neon(1.0, ButtonShape::Pill),
+            heat(Color::WHITE, Color::BLACK, 0.5, true, ButtonShape::Rounded),
+            monochrome(ButtonShape::Square),
+            flat(false, ButtonShape::Rounded),
+        ];
+        for class in classes {
+            let Button::Custom { active, .. } = fade(class, 0.0) else {
+                panic!("faded buttons have a custom style");
+            };
+            let style = active(false, &theme);
+            assert_eq!(style.text_color.map(|color| color.a), Some(0.0));
+            assert_eq!(style.icon_color.map(|color| color.a), Some(0.0));
+            assert_eq!(alpha(style.background), 0.0);
+            assert_eq!(style.border_color.a, 0.0);
+            assert_eq!(style.outline_color.a, 0.0);
+        }
+        let containers = [
+            avatar(120.0, 0.0),
+            pill(ButtonShape::Pill, 0.0),
+            divider(0.0),
+            faded_text(0.0),
+        ];
+        for container in containers {
+            let Container::Custom(style) = container else {
+                panic!("containers have a custom style");
+            };
+            let style = style(&theme);
+            assert!(style.text_color.map_or(true, |color| color.a == 0.0));
+            assert_eq!(alpha(style.background), 0.0);
+        }
+    }
+
+    #[test]
+    fn monochrome_stays_black_on_light_themes() {
+        for theme in [cosmic::Theme::dark(), cosmic::Theme::light()] {
+            let Button::Custom { active, .. } = monochrome(ButtonShape::Rounded) else {
+                panic!("monochrome buttons have a custom style");
+            };
+            let style = active(false, &theme);
+            assert_eq!(style.background, Some(Background::Color(Color::BLACK)));
+        }
+    }
+}
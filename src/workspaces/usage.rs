@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Time spent on every workspace this session, for the heatmap.
+#[derive(Debug, Default)]
+pub struct Usage {
+    /// Time spent on every workspace before it lost focus last.
+    totals: HashMap<u64, Duration>,
+    /// The focused workspace and since when.
+    current: Option<(u64, Instant)>,
+}
+
+impl Usage {
+    /// Records that `focused` has had focus since `now`, adding up the time of the previous one.
+    pub fn focus(&mut self, focused: Option<u64>, now: Instant) {
+        if self.current.map(|(id, _)| id) == focused {
+            return;
+        }
+        if let Some((id, since)) = self.current.take() {
+            *self.totals.entry(id).or_default() += now.saturating_duration_since(since);
+        }
+        self.current = focused.map(|id| (id, now));
+    }
+
+    /// Time spent on `id` this session, including the ongoing visit.
+    pub fn total(&self, id: u64, now: Instant) -> Duration {
+        let past = self.totals.get(&id).copied().unwrap_or_default();
+        match self.current {
+            Some((current, since)) if current == id => past + now.saturating_duration_since(since),
+            _ => past,
+        }
+    }
+
+    /// Moves the time of every workspace to its new id in `ids`, forgetting the others.
+    pub fn remap(&mut self, ids: &HashMap<u64, u64>) {
+        self.totals = self
+            .totals
+            .drain()
+            .filter_map(|(id, total)| Some((*ids.get(&id)?, total)))
+            .collect();
+        self.current = self
+            .current
+            .and_then(|(id, since)| Some((*ids.get(&id)?, since)));
+    }
+
+    /// Usage of every workspace in `ids` from `0.0` for the least used one to `1.0` for the most
+    /// used one.
+    pub fn heat(&self, ids: &[u64], now: Instant) -> HashMap<u64, f32> {
+        let totals: Vec<(u64, f32)> = ids
+            .iter()
+            .map(|&id| (id, self.total(id, now).as_secs_f32()))
+            .collect();
+        let min = totals.iter().map(|(_, t)| *t).fold(f32::INFINITY, f32::min);
+        let max = totals.iter().map(|(_, t)| *t).fold(0.0, f32::max);
+        totals
+            .into_iter()
+            .map(|(id, total)| {
+                let heat = match max > min {
+                    true => (total - min) / (max - min),
+                    false => 0.0,
+                };
+                (id, heat)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heat_is_normalized() {
+        let start = Instant::now();
+        let mut usage = Usage::default();
+        usage.focus(Some(1), start);
+        usage.focus(Some(2), start + Duration::from_secs(10));
+        usage.focus(Some(1), start + Duration::from_secs(15));
+        let now = start + Duration::from_secs(20);
+        assert_eq!(usage.total(1, now), Duration::from_secs(15));
+        assert_eq!(usage.total(2, now), Duration::from_secs(5));
+
+        let heat = usage.heat(&[1, 2, 3], now);
+        assert_eq!(heat[&1], 1.0);
+        assert_eq!(heat[&2], 1.0 / 3.0);
+        assert_eq!(heat[&3], 0.0);
+        assert_eq!(usage.heat(&[3], now)[&3], 0.0);
+    }
+}